@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use sdl2::rect::Rect;
+
+use crate::{Atlas, DrawCommand, DrawData, Entity, EventData, GameHandle};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnimationMode {
+    #[default]
+    Loop,
+    PingPong,
+    Once,
+}
+
+#[derive(Clone, Debug)]
+pub struct AnimationFrame {
+    pub texture: String,
+    pub src: Rect,
+    pub duration: Duration,
+}
+
+pub struct Animation {
+    pub frames: Vec<AnimationFrame>,
+    pub mode: AnimationMode,
+    pub dst: Rect,
+    pub layer: i32,
+    pub angle: f64,
+    pub flip_h: bool,
+    pub flip_v: bool,
+    pub playing: bool,
+    on_finish: Option<fn(&mut GameHandle, Entity)>,
+    current: usize,
+    elapsed: Duration,
+    reverse: bool,
+    finished: bool,
+}
+
+impl Animation {
+    pub fn new(frames: Vec<AnimationFrame>, mode: AnimationMode, dst: Rect) -> Self {
+        Animation {
+            frames,
+            mode,
+            dst,
+            layer: 0,
+            angle: 0.0,
+            flip_h: false,
+            flip_v: false,
+            playing: true,
+            on_finish: None,
+            current: 0,
+            elapsed: Duration::ZERO,
+            reverse: false,
+            finished: false,
+        }
+    }
+
+    // Uses the atlas regions named by the prefix followed by a frame number.
+    // Frames without a duration of their own (e.g. grid atlases) get the
+    // given one.
+    pub fn from_atlas(
+        atlas: &Atlas,
+        prefix: &str,
+        duration: Duration,
+        mode: AnimationMode,
+        dst: Rect,
+    ) -> Self {
+        let frames = atlas
+            .sequence(prefix)
+            .into_iter()
+            .map(|(_, region)| AnimationFrame {
+                texture: atlas.texture.clone(),
+                src: region.rect,
+                duration: region.duration.unwrap_or(duration),
+            })
+            .collect();
+        Animation::new(frames, mode, dst)
+    }
+
+    pub fn layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn on_finish(mut self, callback: fn(&mut GameHandle, Entity)) -> Self {
+        self.on_finish = Some(callback);
+        self
+    }
+
+    pub fn frame(&self) -> Option<&AnimationFrame> {
+        self.frames.get(self.current)
+    }
+
+    pub fn frame_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn restart(&mut self) {
+        self.current = 0;
+        self.elapsed = Duration::ZERO;
+        self.reverse = false;
+        self.finished = false;
+        self.playing = true;
+    }
+
+    // Returns true if the animation finished during this step.
+    fn advance(&mut self, delta: Duration) -> bool {
+        if !self.playing || self.finished || self.frames.is_empty() {
+            return false;
+        }
+
+        // Frames without a duration are skipped. If no frame has one, only a
+        // one-off animation gets anywhere, straight to its end.
+        let all_zero = self.frames.iter().all(|frame| frame.duration.is_zero());
+        if all_zero && self.mode != AnimationMode::Once {
+            return false;
+        }
+
+        self.elapsed += delta;
+        while self.elapsed >= self.frames[self.current].duration {
+            self.elapsed -= self.frames[self.current].duration;
+
+            let last = self.frames.len() - 1;
+            match self.mode {
+                AnimationMode::Loop => self.current = (self.current + 1) % self.frames.len(),
+                AnimationMode::Once if self.current == last => {
+                    self.finished = true;
+                    self.elapsed = Duration::ZERO;
+                    return true;
+                }
+                AnimationMode::Once => self.current += 1,
+                AnimationMode::PingPong if last == 0 => (),
+                AnimationMode::PingPong => {
+                    if (self.reverse && self.current == 0)
+                        || (!self.reverse && self.current == last)
+                    {
+                        self.reverse = !self.reverse;
+                    }
+                    if self.reverse {
+                        self.current -= 1;
+                    } else {
+                        self.current += 1;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+pub(crate) fn animate(game: &mut GameHandle, entity: Entity, animation: &mut Animation) {
+    if animation.advance(game.delta_duration()) {
+        if let Some(callback) = animation.on_finish {
+            callback(game, entity);
+        }
+        let _ = game.push_event(EventData::AnimationFinished { entity });
+    }
+
+    if let Some(frame) = animation.frame() {
+        let data = DrawData::TextureEx {
+            id: frame.texture.clone(),
+            src: Some(frame.src),
+            dst: Some(animation.dst),
+            center: None,
+            angle: animation.angle,
+            flip_h: animation.flip_h,
+            flip_v: animation.flip_v,
+        };
+        game.draw(DrawCommand::new(data).layer(animation.layer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(durations: &[u64], mode: AnimationMode) -> Animation {
+        let frames = durations
+            .iter()
+            .map(|&ms| AnimationFrame {
+                texture: "sheet".to_string(),
+                src: Rect::new(0, 0, 8, 8),
+                duration: Duration::from_millis(ms),
+            })
+            .collect();
+        Animation::new(frames, mode, Rect::new(0, 0, 8, 8))
+    }
+
+    #[test]
+    fn zero_length_frames_are_skipped() {
+        let mut animation = animation(&[0, 100, 0], AnimationMode::Loop);
+        animation.advance(Duration::from_millis(50));
+        assert_eq!(animation.frame_index(), 1);
+        animation.advance(Duration::from_millis(60));
+        assert_eq!(animation.frame_index(), 1);
+        assert_eq!(animation.elapsed, Duration::from_millis(10));
+    }
+
+    #[test]
+    fn animations_without_durations_do_not_hang() {
+        let mut looping = animation(&[0, 0], AnimationMode::Loop);
+        assert!(!looping.advance(Duration::from_millis(50)));
+        assert_eq!(looping.frame_index(), 0);
+
+        let mut once = animation(&[0, 0], AnimationMode::Once);
+        assert!(once.advance(Duration::from_millis(50)));
+        assert!(once.is_finished());
+    }
+}
@@ -0,0 +1,130 @@
+use std::{collections::HashMap, fs, time::Duration};
+
+use sdl2::rect::Rect;
+use serde_json::Value;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AtlasRegion {
+    pub rect: Rect,
+    pub duration: Option<Duration>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Atlas {
+    pub texture: String,
+    regions: HashMap<String, AtlasRegion>,
+}
+
+impl Atlas {
+    pub fn new(texture: &str) -> Self {
+        Atlas {
+            texture: texture.to_string(),
+            regions: HashMap::new(),
+        }
+    }
+
+    // Regions are named by appending their index, in row-major order, to the
+    // prefix, e.g. "player/run_" gives "player/run_0", "player/run_1", ...
+    pub fn grid(
+        texture: &str,
+        prefix: &str,
+        frame_width: u32,
+        frame_height: u32,
+        columns: u32,
+        rows: u32,
+    ) -> Self {
+        let mut atlas = Atlas::new(texture);
+        for i in 0..columns * rows {
+            let rect = Rect::new(
+                (i % columns * frame_width) as i32,
+                (i / columns * frame_height) as i32,
+                frame_width,
+                frame_height,
+            );
+            atlas.add_region(&format!("{prefix}{i}"), rect);
+        }
+        atlas
+    }
+
+    pub fn from_json_file(texture: &str, path: &str) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        Self::from_json(texture, &source)
+    }
+
+    // Accepts the JSON hash and JSON array formats written by TexturePacker
+    // and Aseprite. Aseprite frame durations are kept.
+    pub fn from_json(texture: &str, source: &str) -> Result<Self, String> {
+        let json: Value = serde_json::from_str(source).map_err(|e| e.to_string())?;
+        let frames: Vec<(String, &Value)> = match &json["frames"] {
+            Value::Object(frames) => frames
+                .iter()
+                .map(|(name, frame)| (name.clone(), frame))
+                .collect(),
+            Value::Array(frames) => frames
+                .iter()
+                .map(|frame| {
+                    let name = frame["filename"]
+                        .as_str()
+                        .ok_or("atlas: frame is missing filename")?;
+                    Ok((name.to_string(), frame))
+                })
+                .collect::<Result<_, String>>()?,
+            _ => return Err("atlas: missing frames".to_string()),
+        };
+
+        let mut atlas = Atlas::new(texture);
+        for (name, frame) in frames {
+            let int = |key: &str| {
+                frame["frame"][key]
+                    .as_i64()
+                    .ok_or(format!("atlas: frame {name} is missing {key}"))
+            };
+            let rect = Rect::new(
+                int("x")? as i32,
+                int("y")? as i32,
+                int("w")? as u32,
+                int("h")? as u32,
+            );
+            let duration = frame["duration"].as_u64().map(Duration::from_millis);
+            atlas.regions.insert(name, AtlasRegion { rect, duration });
+        }
+        Ok(atlas)
+    }
+
+    pub fn add_region(&mut self, name: &str, rect: Rect) {
+        let region = AtlasRegion {
+            rect,
+            duration: None,
+        };
+        self.regions.insert(name.to_string(), region);
+    }
+
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.get(name)
+    }
+
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &AtlasRegion)> {
+        self.regions
+            .iter()
+            .map(|(name, region)| (name.as_str(), region))
+    }
+
+    // Returns the regions whose names start with the prefix, sorted by the
+    // number that follows it, e.g. all frames of "player/run_".
+    pub fn sequence(&self, prefix: &str) -> Vec<(&str, &AtlasRegion)> {
+        let mut sequence: Vec<_> = self
+            .regions()
+            .filter_map(|(name, region)| {
+                let index = name.strip_prefix(prefix)?;
+                let digits = index.split(|c: char| !c.is_ascii_digit()).next()?;
+                let index: u32 = digits.parse().ok()?;
+                Some((index, name, region))
+            })
+            .collect();
+        sequence.sort_by_key(|(index, _, _)| *index);
+        sequence
+            .into_iter()
+            .map(|(_, name, region)| (name, region))
+            .collect()
+    }
+}
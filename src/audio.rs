@@ -1,21 +1,188 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use sdl2::{
+    audio::{AudioCallback, AudioDevice, AudioSpecDesired},
+    Sdl,
+};
+
 use crate::{AudioRequest, SharedState};
 
-pub struct AudioModule {}
+pub struct AudioModule {
+    backend: Box<dyn AudioBackend>,
+}
 
 impl AudioModule {
-    pub fn new() -> Self {
-        AudioModule {}
+    pub fn new(backend: Box<dyn AudioBackend>) -> Self {
+        AudioModule { backend }
     }
 
     pub fn update(&mut self, state: &SharedState) -> Result<(), String> {
-        for request in state.take_audio_requests()? {
-            self.handle_request(&request)?;
+        for (tick, request) in state.take_audio_requests()? {
+            self.backend.handle_request(tick, &request)?;
+        }
+        Ok(())
+    }
+}
+
+pub trait AudioBackend {
+    fn handle_request(&mut self, tick: u64, request: &AudioRequest) -> Result<(), String>;
+}
+
+pub struct SdlAudioBackend {
+    device: AudioDevice<Mixer>,
+    sound_cache: HashMap<String, Arc<[f32]>>,
+}
+
+impl SdlAudioBackend {
+    pub fn new(sdl: &Sdl) -> Result<Self, String> {
+        let spec = AudioSpecDesired {
+            freq: Some(44100),
+            channels: Some(1),
+            samples: None,
+        };
+        let device = sdl
+            .audio()?
+            .open_playback(None, &spec, |_| Mixer { voices: vec![] })?;
+        device.resume();
+
+        Ok(SdlAudioBackend {
+            device,
+            sound_cache: HashMap::new(),
+        })
+    }
+
+    fn add_sound(&mut self, id: &str, samples: Arc<[f32]>) -> Result<(), String> {
+        if self.sound_cache.contains_key(id) {
+            return Err(format!("sound already exists: {id}"));
         }
+
+        self.sound_cache.insert(id.to_string(), samples);
         Ok(())
     }
 
-    fn handle_request(&mut self, request: &AudioRequest) -> Result<(), String> {
-        let _ = request;
+    fn unload_sound(&mut self, id: &str) -> Result<(), String> {
+        match self.sound_cache.remove(id) {
+            Some(_) => Ok(()),
+            None => Err(format!("sound not found: {id}")),
+        }
+    }
+
+    fn play(&mut self, id: &str, volume: f32) -> Result<(), String> {
+        let samples = match self.sound_cache.get(id) {
+            Some(samples) => Arc::clone(samples),
+            None => return Err(format!("sound not found: {id}")),
+        };
+        self.device.lock().voices.push(Voice {
+            samples,
+            position: 0,
+            volume,
+        });
         Ok(())
     }
 }
+
+impl AudioBackend for SdlAudioBackend {
+    fn handle_request(&mut self, _tick: u64, request: &AudioRequest) -> Result<(), String> {
+        match request {
+            AudioRequest::Synthesize { id, params } => {
+                let samples = params.render(self.device.spec().freq as u32);
+                self.add_sound(id, samples.into())?
+            }
+            AudioRequest::Play { id, volume } => self.play(id, *volume)?,
+            AudioRequest::StopAll => self.device.lock().voices.clear(),
+            AudioRequest::Unload { id } => self.unload_sound(id)?,
+        };
+        Ok(())
+    }
+}
+
+pub struct NullAudioBackend {
+    log: AudioLog,
+}
+
+impl NullAudioBackend {
+    pub fn new() -> Self {
+        NullAudioBackend {
+            log: AudioLog::default(),
+        }
+    }
+
+    pub fn log(&self) -> AudioLog {
+        self.log.clone()
+    }
+}
+
+impl Default for NullAudioBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioBackend for NullAudioBackend {
+    fn handle_request(&mut self, tick: u64, request: &AudioRequest) -> Result<(), String> {
+        self.log
+            .0
+            .lock()
+            .map_err(|e| e.to_string())?
+            .push((tick, request.clone()));
+        Ok(())
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AudioLog(Arc<Mutex<Vec<(u64, AudioRequest)>>>);
+
+impl AudioLog {
+    pub fn entries(&self) -> Result<Vec<(u64, AudioRequest)>, String> {
+        Ok(self.0.lock().map_err(|e| e.to_string())?.clone())
+    }
+
+    pub fn played(&self) -> Result<Vec<(u64, String)>, String> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter_map(|(tick, request)| match request {
+                AudioRequest::Play { id, .. } => Some((tick, id)),
+                _ => None,
+            })
+            .collect())
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        self.0.lock().map_err(|e| e.to_string())?.clear();
+        Ok(())
+    }
+}
+
+struct Mixer {
+    voices: Vec<Voice>,
+}
+
+struct Voice {
+    samples: Arc<[f32]>,
+    position: usize,
+    volume: f32,
+}
+
+impl AudioCallback for Mixer {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        out.fill(0.0);
+        for voice in self.voices.iter_mut() {
+            let remaining = &voice.samples[voice.position..];
+            for (out, sample) in out.iter_mut().zip(remaining) {
+                *out += sample * voice.volume;
+            }
+            voice.position += remaining.len().min(out.len());
+        }
+        self.voices
+            .retain(|voice| voice.position < voice.samples.len());
+        for out in out.iter_mut() {
+            *out = out.clamp(-1.0, 1.0);
+        }
+    }
+}
@@ -0,0 +1,614 @@
+use std::f32::consts::TAU;
+
+use sdl2::rect::{Point, Rect};
+
+use crate::DrawData;
+
+// The world point (x, y) is shown at the screen point (offset_x, offset_y),
+// scaled by zoom and rotated by rotation degrees around it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    pub x: f32,
+    pub y: f32,
+    pub zoom: f32,
+    pub rotation: f32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+            rotation: 0.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+        }
+    }
+}
+
+impl Camera {
+    pub fn new(x: f32, y: f32) -> Self {
+        Camera {
+            x,
+            y,
+            ..Default::default()
+        }
+    }
+
+    pub fn centered(x: f32, y: f32, screen_width: u32, screen_height: u32) -> Self {
+        Camera {
+            x,
+            y,
+            offset_x: screen_width as f32 / 2.0,
+            offset_y: screen_height as f32 / 2.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn lerp(&self, other: &Camera, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Camera {
+            x: lerp(self.x, other.x),
+            y: lerp(self.y, other.y),
+            zoom: lerp(self.zoom, other.zoom),
+            rotation: lerp(self.rotation, other.rotation),
+            offset_x: lerp(self.offset_x, other.offset_x),
+            offset_y: lerp(self.offset_y, other.offset_y),
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Camera::default()
+    }
+
+    pub fn world_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (dx, dy) = ((x - self.x) * self.zoom, (y - self.y) * self.zoom);
+        (
+            dx * cos - dy * sin + self.offset_x,
+            dx * sin + dy * cos + self.offset_y,
+        )
+    }
+
+    pub fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (dx, dy) = (x - self.offset_x, y - self.offset_y);
+        (
+            (dx * cos + dy * sin) / self.zoom + self.x,
+            (-dx * sin + dy * cos) / self.zoom + self.y,
+        )
+    }
+
+    // Returns the axis-aligned world rectangle covering a screen of the given
+    // size, for culling.
+    pub fn visible_area(&self, screen_width: u32, screen_height: u32) -> (f32, f32, f32, f32) {
+        let (w, h) = (screen_width as f32, screen_height as f32);
+        let corners =
+            [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)].map(|(x, y)| self.screen_to_world(x, y));
+        let min_x = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min);
+        let min_y = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min);
+        let max_x = corners.iter().map(|c| c.0).fold(f32::MIN, f32::max);
+        let max_y = corners.iter().map(|c| c.1).fold(f32::MIN, f32::max);
+        (min_x, min_y, max_x - min_x, max_y - min_y)
+    }
+
+    pub fn apply(&self, data: &DrawData) -> DrawData {
+        let point = |x: i16, y: i16| -> (i16, i16) {
+            let (x, y) = self.world_to_screen(x as f32, y as f32);
+            (x.round() as i16, y.round() as i16)
+        };
+        let length = |length: i16| (length as f32 * self.zoom).round() as i16;
+        let angle = |angle: i16| angle + self.rotation.round() as i16;
+
+        match data.clone() {
+            DrawData::Rectangle { rect, color }
+            | DrawData::RoundedRectangle { rect, color, .. }
+                if self.rotation != 0.0 =>
+            {
+                DrawData::Polygon {
+                    points: self.rect_points(rect),
+                    color,
+                }
+            }
+            DrawData::FilledRectangle { rect, color }
+            | DrawData::FilledRoundedRectangle { rect, color, .. }
+                if self.rotation != 0.0 =>
+            {
+                DrawData::FilledPolygon {
+                    points: self.rect_points(rect),
+                    color,
+                }
+            }
+            DrawData::Rectangle { rect, color } => DrawData::Rectangle {
+                rect: self.rect(rect),
+                color,
+            },
+            DrawData::FilledRectangle { rect, color } => DrawData::FilledRectangle {
+                rect: self.rect(rect),
+                color,
+            },
+            DrawData::RoundedRectangle { rect, rad, color } => DrawData::RoundedRectangle {
+                rect: self.rect(rect),
+                rad: length(rad),
+                color,
+            },
+            DrawData::FilledRoundedRectangle { rect, rad, color } => {
+                DrawData::FilledRoundedRectangle {
+                    rect: self.rect(rect),
+                    rad: length(rad),
+                    color,
+                }
+            }
+            DrawData::Point { x, y, color } => {
+                let (x, y) = point(x, y);
+                DrawData::Point { x, y, color }
+            }
+            DrawData::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            } => {
+                let ((x1, y1), (x2, y2)) = (point(x1, y1), point(x2, y2));
+                DrawData::Line {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    color,
+                }
+            }
+            DrawData::AaLine {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            } => {
+                let ((x1, y1), (x2, y2)) = (point(x1, y1), point(x2, y2));
+                DrawData::AaLine {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    color,
+                }
+            }
+            DrawData::ThickLine {
+                x1,
+                y1,
+                x2,
+                y2,
+                width,
+                color,
+            } => {
+                let ((x1, y1), (x2, y2)) = (point(x1, y1), point(x2, y2));
+                DrawData::ThickLine {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    width: (width as f32 * self.zoom).round().clamp(1.0, 255.0) as u8,
+                    color,
+                }
+            }
+            DrawData::Circle { x, y, rad, color } => {
+                let (x, y) = point(x, y);
+                DrawData::Circle {
+                    x,
+                    y,
+                    rad: length(rad),
+                    color,
+                }
+            }
+            DrawData::AaCircle { x, y, rad, color } => {
+                let (x, y) = point(x, y);
+                DrawData::AaCircle {
+                    x,
+                    y,
+                    rad: length(rad),
+                    color,
+                }
+            }
+            DrawData::FilledCircle { x, y, rad, color } => {
+                let (x, y) = point(x, y);
+                DrawData::FilledCircle {
+                    x,
+                    y,
+                    rad: length(rad),
+                    color,
+                }
+            }
+            DrawData::Ellipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } if self.rotation != 0.0 => DrawData::Polygon {
+                points: self.ellipse_points(x, y, rx, ry),
+                color,
+            },
+            DrawData::AaEllipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } if self.rotation != 0.0 => DrawData::AaPolygon {
+                points: self.ellipse_points(x, y, rx, ry),
+                color,
+            },
+            DrawData::FilledEllipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } if self.rotation != 0.0 => DrawData::FilledPolygon {
+                points: self.ellipse_points(x, y, rx, ry),
+                color,
+            },
+            DrawData::Ellipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } => {
+                let (x, y) = point(x, y);
+                DrawData::Ellipse {
+                    x,
+                    y,
+                    rx: length(rx),
+                    ry: length(ry),
+                    color,
+                }
+            }
+            DrawData::AaEllipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } => {
+                let (x, y) = point(x, y);
+                DrawData::AaEllipse {
+                    x,
+                    y,
+                    rx: length(rx),
+                    ry: length(ry),
+                    color,
+                }
+            }
+            DrawData::FilledEllipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } => {
+                let (x, y) = point(x, y);
+                DrawData::FilledEllipse {
+                    x,
+                    y,
+                    rx: length(rx),
+                    ry: length(ry),
+                    color,
+                }
+            }
+            DrawData::Arc {
+                x,
+                y,
+                rad,
+                start,
+                end,
+                color,
+            } => {
+                let (x, y) = point(x, y);
+                DrawData::Arc {
+                    x,
+                    y,
+                    rad: length(rad),
+                    start: angle(start),
+                    end: angle(end),
+                    color,
+                }
+            }
+            DrawData::Pie {
+                x,
+                y,
+                rad,
+                start,
+                end,
+                color,
+            } => {
+                let (x, y) = point(x, y);
+                DrawData::Pie {
+                    x,
+                    y,
+                    rad: length(rad),
+                    start: angle(start),
+                    end: angle(end),
+                    color,
+                }
+            }
+            DrawData::FilledPie {
+                x,
+                y,
+                rad,
+                start,
+                end,
+                color,
+            } => {
+                let (x, y) = point(x, y);
+                DrawData::FilledPie {
+                    x,
+                    y,
+                    rad: length(rad),
+                    start: angle(start),
+                    end: angle(end),
+                    color,
+                }
+            }
+            DrawData::Triangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                color,
+            } => {
+                let ((x1, y1), (x2, y2), (x3, y3)) = (point(x1, y1), point(x2, y2), point(x3, y3));
+                DrawData::Triangle {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    color,
+                }
+            }
+            DrawData::AaTriangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                color,
+            } => {
+                let ((x1, y1), (x2, y2), (x3, y3)) = (point(x1, y1), point(x2, y2), point(x3, y3));
+                DrawData::AaTriangle {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    color,
+                }
+            }
+            DrawData::FilledTriangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                color,
+            } => {
+                let ((x1, y1), (x2, y2), (x3, y3)) = (point(x1, y1), point(x2, y2), point(x3, y3));
+                DrawData::FilledTriangle {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    x3,
+                    y3,
+                    color,
+                }
+            }
+            DrawData::Polygon { points, color } => DrawData::Polygon {
+                points: points.into_iter().map(|(x, y)| point(x, y)).collect(),
+                color,
+            },
+            DrawData::AaPolygon { points, color } => DrawData::AaPolygon {
+                points: points.into_iter().map(|(x, y)| point(x, y)).collect(),
+                color,
+            },
+            DrawData::FilledPolygon { points, color } => DrawData::FilledPolygon {
+                points: points.into_iter().map(|(x, y)| point(x, y)).collect(),
+                color,
+            },
+            DrawData::Texture {
+                id,
+                src,
+                dst: Some(dst),
+            } => {
+                let (dst, center, angle) = self.texture(dst, None, 0.0);
+                DrawData::TextureEx {
+                    id,
+                    src,
+                    dst: Some(dst),
+                    center: Some(center),
+                    angle,
+                    flip_h: false,
+                    flip_v: false,
+                }
+            }
+            DrawData::TextureEx {
+                id,
+                src,
+                dst: Some(dst),
+                center,
+                angle,
+                flip_h,
+                flip_v,
+            } => {
+                let (dst, center, angle) = self.texture(dst, center, angle);
+                DrawData::TextureEx {
+                    id,
+                    src,
+                    dst: Some(dst),
+                    center: Some(center),
+                    angle,
+                    flip_h,
+                    flip_v,
+                }
+            }
+            DrawData::Sprite {
+                name,
+                dst: Some(dst),
+            } => {
+                let (dst, center, angle) = self.texture(dst, None, 0.0);
+                DrawData::SpriteEx {
+                    name,
+                    dst: Some(dst),
+                    center: Some(center),
+                    angle,
+                    flip_h: false,
+                    flip_v: false,
+                }
+            }
+            DrawData::SpriteEx {
+                name,
+                dst: Some(dst),
+                center,
+                angle,
+                flip_h,
+                flip_v,
+            } => {
+                let (dst, center, angle) = self.texture(dst, center, angle);
+                DrawData::SpriteEx {
+                    name,
+                    dst: Some(dst),
+                    center: Some(center),
+                    angle,
+                    flip_h,
+                    flip_v,
+                }
+            }
+            // Text can't be scaled or rotated as draw data, so it is only
+            // moved here. The window module places its glyphs through the
+            // camera instead.
+            DrawData::Text {
+                font,
+                text,
+                x,
+                y,
+                color,
+                size,
+            } => {
+                let (x, y) = self.world_to_screen(x as f32, y as f32);
+                DrawData::Text {
+                    font,
+                    text,
+                    x: x.round() as i32,
+                    y: y.round() as i32,
+                    color,
+                    size,
+                }
+            }
+            DrawData::BitmapText {
+                font,
+                text,
+                x,
+                y,
+                align,
+                max_width,
+                color,
+                colors,
+            } => {
+                let (x, y) = self.world_to_screen(x as f32, y as f32);
+                DrawData::BitmapText {
+                    font,
+                    text,
+                    x: x.round() as i32,
+                    y: y.round() as i32,
+                    align,
+                    max_width,
+                    color,
+                    colors,
+                }
+            }
+            DrawData::Tilemap { id, layer, x, y } => {
+                let (x, y) = self.world_to_screen(x as f32, y as f32);
+                DrawData::Tilemap {
+                    id,
+                    layer,
+                    x: x.round() as i32,
+                    y: y.round() as i32,
+                }
+            }
+            data => data,
+        }
+    }
+
+    fn rect(&self, rect: Rect) -> Rect {
+        let (x, y) = self.world_to_screen(rect.x() as f32, rect.y() as f32);
+        Rect::new(
+            x.round() as i32,
+            y.round() as i32,
+            (rect.width() as f32 * self.zoom).round() as u32,
+            (rect.height() as f32 * self.zoom).round() as u32,
+        )
+    }
+
+    fn rect_points(&self, rect: Rect) -> Vec<(i16, i16)> {
+        [
+            (rect.left(), rect.top()),
+            (rect.right(), rect.top()),
+            (rect.right(), rect.bottom()),
+            (rect.left(), rect.bottom()),
+        ]
+        .into_iter()
+        .map(|(x, y)| {
+            let (x, y) = self.world_to_screen(x as f32, y as f32);
+            (x.round() as i16, y.round() as i16)
+        })
+        .collect()
+    }
+
+    fn ellipse_points(&self, x: i16, y: i16, rx: i16, ry: i16) -> Vec<(i16, i16)> {
+        const SEGMENTS: usize = 32;
+        (0..SEGMENTS)
+            .map(|i| {
+                let (sin, cos) = (i as f32 / SEGMENTS as f32 * TAU).sin_cos();
+                let (x, y) =
+                    self.world_to_screen(x as f32 + cos * rx as f32, y as f32 + sin * ry as f32);
+                (x.round() as i16, y.round() as i16)
+            })
+            .collect()
+    }
+
+    // Textures are rotated around their pivot, which is placed at its
+    // transformed position, so the result matches rotating the whole world.
+    pub(crate) fn texture(
+        &self,
+        dst: Rect,
+        center: Option<Point>,
+        angle: f64,
+    ) -> (Rect, Point, f64) {
+        let center = center.unwrap_or(Point::new(dst.width() as i32 / 2, dst.height() as i32 / 2));
+        let (pivot_x, pivot_y) =
+            self.world_to_screen((dst.x() + center.x()) as f32, (dst.y() + center.y()) as f32);
+        let center_x = center.x() as f32 * self.zoom;
+        let center_y = center.y() as f32 * self.zoom;
+
+        let dst = Rect::new(
+            (pivot_x - center_x).round() as i32,
+            (pivot_y - center_y).round() as i32,
+            (dst.width() as f32 * self.zoom).round() as u32,
+            (dst.height() as f32 * self.zoom).round() as u32,
+        );
+        let center = Point::new(center_x.round() as i32, center_y.round() as i32);
+        (dst, center, angle + self.rotation as f64)
+    }
+}
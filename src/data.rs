@@ -1,24 +1,28 @@
 use sdl2::{
     pixels::Color,
     rect::{Point, Rect},
+    render::BlendMode,
 };
 
 use std::{
     mem,
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Mutex, MutexGuard,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
     },
+    time::Duration,
 };
 
-use crate::event::EventData;
+use crate::{event::EventData, Atlas, Camera, SfxrParams, TextAlign, Tilemap};
 
 pub struct SharedState {
     running: AtomicBool,
+    tick: AtomicU64,
     events: Mutex<Vec<EventData>>,
-    audio_requests: Mutex<Vec<AudioRequest>>,
+    audio_requests: Mutex<Vec<(u64, AudioRequest)>>,
     window_requests: Mutex<Vec<WindowRequest>>,
-    draw_data: Mutex<Vec<DrawData>>,
+    world_requests: Mutex<Vec<WorldRequest>>,
+    draw_data: Mutex<DrawFrame>,
     new_draw_data: AtomicBool,
 }
 
@@ -26,10 +30,12 @@ impl SharedState {
     pub fn new() -> Self {
         SharedState {
             running: true.into(),
+            tick: 0.into(),
             events: vec![].into(),
             audio_requests: vec![].into(),
             window_requests: vec![].into(),
-            draw_data: vec![].into(),
+            world_requests: vec![].into(),
+            draw_data: DrawFrame::default().into(),
             new_draw_data: true.into(),
         }
     }
@@ -42,6 +48,14 @@ impl SharedState {
         self.running.store(false, Ordering::Release);
     }
 
+    pub fn tick(&self) -> u64 {
+        self.tick.load(Ordering::Acquire)
+    }
+
+    pub fn next_tick(&self) {
+        self.tick.fetch_add(1, Ordering::AcqRel);
+    }
+
     pub fn push_event(&self, event: EventData) -> Result<(), String> {
         self.events.lock().map_err(|e| e.to_string())?.push(event);
         Ok(())
@@ -57,7 +71,7 @@ impl SharedState {
         self.audio_requests
             .lock()
             .map_err(|e| e.to_string())?
-            .push(request);
+            .push((self.tick(), request));
         Ok(())
     }
 
@@ -69,7 +83,15 @@ impl SharedState {
         Ok(())
     }
 
-    pub fn take_audio_requests(&self) -> Result<Vec<AudioRequest>, String> {
+    pub fn send_world_request(&self, request: WorldRequest) -> Result<(), String> {
+        self.world_requests
+            .lock()
+            .map_err(|e| e.to_string())?
+            .push(request);
+        Ok(())
+    }
+
+    pub fn take_audio_requests(&self) -> Result<Vec<(u64, AudioRequest)>, String> {
         Ok(mem::take(
             &mut *self.audio_requests.lock().map_err(|e| e.to_string())?,
         ))
@@ -81,7 +103,13 @@ impl SharedState {
         ))
     }
 
-    pub fn set_draw_data(&self, draw_data: Vec<DrawData>) -> Result<(), String> {
+    pub fn take_world_requests(&self) -> Result<Vec<WorldRequest>, String> {
+        Ok(mem::take(
+            &mut *self.world_requests.lock().map_err(|e| e.to_string())?,
+        ))
+    }
+
+    pub fn set_draw_data(&self, draw_data: DrawFrame) -> Result<(), String> {
         let _ = mem::replace(
             &mut *self.draw_data.lock().map_err(|e| e.to_string())?,
             draw_data,
@@ -90,29 +118,104 @@ impl SharedState {
         Ok(())
     }
 
-    pub fn lock_draw_data(&self) -> Result<Option<MutexGuard<Vec<DrawData>>>, String> {
-        if self.new_draw_data.load(Ordering::Acquire) {
-            let data = self.draw_data.lock().map_err(|e| e.to_string());
-            self.new_draw_data.store(false, Ordering::Release);
-            data.map(|data| Some(data))
+    pub fn take_draw_data(&self) -> Result<Option<DrawFrame>, String> {
+        if self.new_draw_data.swap(false, Ordering::AcqRel) {
+            Ok(Some(mem::take(
+                &mut *self.draw_data.lock().map_err(|e| e.to_string())?,
+            )))
         } else {
             Ok(None)
         }
     }
 }
 
-#[derive(Debug)]
-pub enum AudioRequest {}
+#[derive(Clone, Debug)]
+pub enum AudioRequest {
+    Play { id: String, volume: f32 },
+    StopAll,
+    Synthesize { id: String, params: SfxrParams },
+    Unload { id: String },
+}
 
 #[derive(Debug)]
 pub enum WindowRequest {
+    CreateTarget {
+        id: String,
+        width: u32,
+        height: u32,
+    },
     DisableFullscreen,
     EnableDesktopFullscreen,
     EnableFullscreen,
-    // LoadTexture{id: String, path: String},
-    // LoadTextureBytes{id: String, bytes: Vec<u8>},
+    LoadAtlas(Atlas),
+    LoadBitmapFont {
+        id: String,
+        path: String,
+    },
+    LoadFont {
+        id: String,
+        path: String,
+    },
+    LoadGridFont {
+        id: String,
+        texture: String,
+        char_width: u32,
+        char_height: u32,
+        columns: u32,
+        chars: String,
+    },
+    LoadTexture {
+        id: String,
+        path: String,
+    },
+    LoadTilemap {
+        id: String,
+        map: Tilemap,
+    },
+    LoadTextureBytes {
+        id: String,
+        bytes: Vec<u8>,
+    },
     Resize(u32, u32),
+    Screenshot {
+        path: String,
+    },
     SetBackgroundColor(Color),
+    SetLogicalSize(u32, u32),
+    SetScaleMode(ScaleMode),
+    // Writes every drawn frame to the directory as 000000.png, 000001.png, ...
+    StartCapture {
+        dir: String,
+    },
+    StopCapture,
+    UnloadAtlas {
+        texture: String,
+    },
+    UnloadBitmapFont {
+        id: String,
+    },
+    UnloadFont {
+        id: String,
+    },
+    UnloadTexture {
+        id: String,
+    },
+    UnloadTilemap {
+        id: String,
+    },
+}
+
+// Handled by the world before the next tick.
+#[derive(Clone, Debug)]
+pub enum WorldRequest {
+    SetTicksPerSecond(u16),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScaleMode {
+    #[default]
+    Letterbox,
+    Integer,
 }
 
 pub trait GameRequest {
@@ -131,7 +234,170 @@ impl GameRequest for WindowRequest {
     }
 }
 
-#[derive(Debug)]
+impl GameRequest for WorldRequest {
+    fn send(self, state: &SharedState) -> Result<(), String> {
+        state.send_world_request(self)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DrawFrame {
+    pub commands: Vec<DrawCommand>,
+    pub camera: Camera,
+    pub previous_camera: Camera,
+    pub tick_duration: Duration,
+}
+
+impl DrawFrame {
+    pub fn is_interpolated(&self) -> bool {
+        self.camera != self.previous_camera
+            || self
+                .commands
+                .iter()
+                .any(|command| command.transforms.is_some())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrawSpace {
+    #[default]
+    World,
+    Screen,
+}
+
+#[derive(Clone, Debug)]
+pub struct DrawCommand {
+    pub data: DrawData,
+    pub layer: i32,
+    pub space: DrawSpace,
+    pub target: Option<String>,
+    pub style: DrawStyle,
+    pub transforms: Option<(Transform, Transform)>,
+}
+
+// Alpha and color mod multiply the colors of everything drawn but clears. The
+// blend mode applies to textures, sprites, text, rectangles and clears. The
+// other primitives always blend translucent colors, so setting a mode other
+// than Blend on them is an error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawStyle {
+    pub alpha: u8,
+    pub color_mod: Color,
+    pub blend_mode: Option<BlendMode>,
+}
+
+impl Default for DrawStyle {
+    fn default() -> Self {
+        DrawStyle {
+            alpha: 255,
+            color_mod: Color::WHITE,
+            blend_mode: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Transform {
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32,
+}
+
+impl Transform {
+    pub fn new(x: f32, y: f32, angle: f32) -> Self {
+        Transform { x, y, angle }
+    }
+
+    pub fn lerp(&self, other: &Transform, t: f32) -> Self {
+        Transform {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+            angle: self.angle + (other.angle - self.angle) * t,
+        }
+    }
+}
+
+impl DrawCommand {
+    pub fn new(data: DrawData) -> Self {
+        DrawCommand {
+            data,
+            layer: 0,
+            space: DrawSpace::World,
+            target: None,
+            style: DrawStyle::default(),
+            transforms: None,
+        }
+    }
+
+    // Lower layers are drawn first. Within a layer, draws keep the order they
+    // were pushed in, and SDL batches consecutive copies from one texture.
+    pub fn layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn screen(mut self) -> Self {
+        self.space = DrawSpace::Screen;
+        self
+    }
+
+    // Draws into the render target created with `WindowRequest::CreateTarget`
+    // instead of the window. Targets keep their contents between frames and
+    // are drawn before the window, so the window can use them as textures in
+    // the same frame.
+    pub fn target(mut self, id: &str) -> Self {
+        self.target = Some(id.to_string());
+        self
+    }
+
+    pub fn alpha(mut self, alpha: u8) -> Self {
+        self.style.alpha = alpha;
+        self
+    }
+
+    pub fn color_mod(mut self, color: Color) -> Self {
+        self.style.color_mod = color;
+        self
+    }
+
+    pub fn blend_mode(mut self, mode: BlendMode) -> Self {
+        self.style.blend_mode = Some(mode);
+        self
+    }
+
+    // The draw data describes the object at `current`. Between ticks it is
+    // moved back towards `previous` by the remaining fraction of the tick.
+    pub fn interpolate(mut self, previous: Transform, current: Transform) -> Self {
+        self.transforms = Some((previous, current));
+        self
+    }
+
+    pub fn interpolated(&self, t: f32) -> DrawData {
+        match self.transforms {
+            Some((previous, current)) if previous != current => {
+                let transform = previous.lerp(&current, t);
+                Camera {
+                    x: current.x,
+                    y: current.y,
+                    rotation: transform.angle - current.angle,
+                    offset_x: transform.x,
+                    offset_y: transform.y,
+                    ..Default::default()
+                }
+                .apply(&self.data)
+            }
+            _ => self.data.clone(),
+        }
+    }
+}
+
+impl From<DrawData> for DrawCommand {
+    fn from(data: DrawData) -> Self {
+        DrawCommand::new(data)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub enum DrawData {
     Rectangle {
         rect: Rect,
@@ -141,18 +407,145 @@ pub enum DrawData {
         rect: Rect,
         color: Color,
     },
+    RoundedRectangle {
+        rect: Rect,
+        rad: i16,
+        color: Color,
+    },
+    FilledRoundedRectangle {
+        rect: Rect,
+        rad: i16,
+        color: Color,
+    },
+    Point {
+        x: i16,
+        y: i16,
+        color: Color,
+    },
+    Line {
+        x1: i16,
+        y1: i16,
+        x2: i16,
+        y2: i16,
+        color: Color,
+    },
+    AaLine {
+        x1: i16,
+        y1: i16,
+        x2: i16,
+        y2: i16,
+        color: Color,
+    },
+    ThickLine {
+        x1: i16,
+        y1: i16,
+        x2: i16,
+        y2: i16,
+        width: u8,
+        color: Color,
+    },
     Circle {
         x: i16,
         y: i16,
         rad: i16,
         color: Color,
     },
+    AaCircle {
+        x: i16,
+        y: i16,
+        rad: i16,
+        color: Color,
+    },
     FilledCircle {
         x: i16,
         y: i16,
         rad: i16,
         color: Color,
     },
+    Ellipse {
+        x: i16,
+        y: i16,
+        rx: i16,
+        ry: i16,
+        color: Color,
+    },
+    AaEllipse {
+        x: i16,
+        y: i16,
+        rx: i16,
+        ry: i16,
+        color: Color,
+    },
+    FilledEllipse {
+        x: i16,
+        y: i16,
+        rx: i16,
+        ry: i16,
+        color: Color,
+    },
+    Arc {
+        x: i16,
+        y: i16,
+        rad: i16,
+        start: i16,
+        end: i16,
+        color: Color,
+    },
+    Pie {
+        x: i16,
+        y: i16,
+        rad: i16,
+        start: i16,
+        end: i16,
+        color: Color,
+    },
+    FilledPie {
+        x: i16,
+        y: i16,
+        rad: i16,
+        start: i16,
+        end: i16,
+        color: Color,
+    },
+    Triangle {
+        x1: i16,
+        y1: i16,
+        x2: i16,
+        y2: i16,
+        x3: i16,
+        y3: i16,
+        color: Color,
+    },
+    AaTriangle {
+        x1: i16,
+        y1: i16,
+        x2: i16,
+        y2: i16,
+        x3: i16,
+        y3: i16,
+        color: Color,
+    },
+    FilledTriangle {
+        x1: i16,
+        y1: i16,
+        x2: i16,
+        y2: i16,
+        x3: i16,
+        y3: i16,
+        color: Color,
+    },
+    Polygon {
+        points: Vec<(i16, i16)>,
+        color: Color,
+    },
+    AaPolygon {
+        points: Vec<(i16, i16)>,
+        color: Color,
+    },
+    FilledPolygon {
+        points: Vec<(i16, i16)>,
+        color: Color,
+    },
     Texture {
         id: String,
         src: Option<Rect>,
@@ -167,4 +560,57 @@ pub enum DrawData {
         flip_h: bool,
         flip_v: bool,
     },
+    Sprite {
+        name: String,
+        dst: Option<Rect>,
+    },
+    SpriteEx {
+        name: String,
+        dst: Option<Rect>,
+        center: Option<Point>,
+        angle: f64,
+        flip_h: bool,
+        flip_v: bool,
+    },
+    Text {
+        font: String,
+        text: String,
+        x: i32,
+        y: i32,
+        color: Color,
+        size: u16,
+    },
+    BitmapText {
+        font: String,
+        text: String,
+        x: i32,
+        y: i32,
+        align: TextAlign,
+        max_width: Option<u32>,
+        color: Color,
+        colors: Vec<Color>,
+    },
+    Tilemap {
+        id: String,
+        layer: Option<String>,
+        x: i32,
+        y: i32,
+    },
+    Clear {
+        color: Color,
+    },
+}
+
+impl DrawData {
+    pub fn on_layer(self, layer: i32) -> DrawCommand {
+        DrawCommand::new(self).layer(layer)
+    }
+
+    pub fn on_screen(self) -> DrawCommand {
+        DrawCommand::new(self).screen()
+    }
+
+    pub fn on_target(self, id: &str) -> DrawCommand {
+        DrawCommand::new(self).target(id)
+    }
 }
@@ -1,10 +1,11 @@
 use sdl2::{
     event::Event,
     keyboard::{Keycode, Mod, Scancode},
+    mouse::MouseButton,
     EventPump,
 };
 
-use crate::SharedState;
+use crate::{Entity, SharedState, TimerId, TweenId};
 
 pub struct EventModule {
     event_pump: EventPump,
@@ -37,12 +38,40 @@ impl EventModule {
     }
 }
 
+#[derive(Clone, Debug)]
 pub enum EventData {
     KeyDown {
         keycode: Keycode,
         scancode: Scancode,
         keymod: Mod,
     },
+    MouseMotion {
+        x: i32,
+        y: i32,
+    },
+    MouseButtonDown {
+        button: MouseButton,
+        x: i32,
+        y: i32,
+    },
+    MouseButtonUp {
+        button: MouseButton,
+        x: i32,
+        y: i32,
+    },
+    AnimationFinished {
+        entity: Entity,
+    },
+    TimerFired {
+        id: TimerId,
+    },
+    EntityTimerFired {
+        entity: Entity,
+    },
+    TweenFinished {
+        entity: Entity,
+        id: TweenId,
+    },
 }
 
 impl TryFrom<Event> for EventData {
@@ -60,6 +89,21 @@ impl TryFrom<Event> for EventData {
                 scancode,
                 keymod,
             }),
+            Event::MouseMotion { x, y, .. } => Ok(EventData::MouseMotion { x, y }),
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            } => Ok(EventData::MouseButtonDown {
+                button: mouse_btn,
+                x,
+                y,
+            }),
+            Event::MouseButtonUp {
+                mouse_btn, x, y, ..
+            } => Ok(EventData::MouseButtonUp {
+                button: mouse_btn,
+                x,
+                y,
+            }),
             _ => Err(()),
         }
     }
@@ -0,0 +1,393 @@
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use roxmltree::{Document, Node};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Glyph {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub x_advance: i32,
+    pub page: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct BitmapFont {
+    pub line_height: u32,
+    pub pages: Vec<String>,
+    glyphs: HashMap<char, Glyph>,
+    kernings: HashMap<(char, char), i32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlacedGlyph {
+    pub glyph: Glyph,
+    pub x: i32,
+    pub y: i32,
+    pub index: usize,
+}
+
+impl BitmapFont {
+    pub fn grid(
+        texture: &str,
+        char_width: u32,
+        char_height: u32,
+        columns: u32,
+        chars: &str,
+    ) -> Self {
+        let glyphs = chars
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let glyph = Glyph {
+                    x: (i as u32 % columns * char_width) as i32,
+                    y: (i as u32 / columns * char_height) as i32,
+                    width: char_width,
+                    height: char_height,
+                    x_offset: 0,
+                    y_offset: 0,
+                    x_advance: char_width as i32,
+                    page: 0,
+                };
+                (c, glyph)
+            })
+            .collect();
+
+        BitmapFont {
+            line_height: char_height,
+            pages: vec![texture.to_string()],
+            glyphs,
+            kernings: HashMap::new(),
+        }
+    }
+
+    pub fn from_fnt_file(path: &str) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let mut font = Self::from_fnt(&source)?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        for page in font.pages.iter_mut() {
+            *page = dir.join(&page).to_string_lossy().into_owned();
+        }
+        Ok(font)
+    }
+
+    // Accepts both the text and the XML flavour of the BMFont format. Page
+    // entries keep the file names as written in the source.
+    pub fn from_fnt(source: &str) -> Result<Self, String> {
+        let mut font = BitmapFont {
+            line_height: 0,
+            pages: vec![],
+            glyphs: HashMap::new(),
+            kernings: HashMap::new(),
+        };
+
+        if source.trim_start().starts_with('<') {
+            let document = Document::parse(source).map_err(|e| format!("bitmap font: {e}"))?;
+            for node in document.descendants().filter(Node::is_element) {
+                let attributes = node
+                    .attributes()
+                    .map(|attribute| (attribute.name(), attribute.value()))
+                    .collect();
+                font.add_entry(node.tag_name().name(), &attributes)?;
+            }
+        } else {
+            for line in source.lines() {
+                if let Some((tag, attributes)) = parse_fnt_line(line) {
+                    font.add_entry(tag, &attributes)?;
+                }
+            }
+        }
+
+        if font.pages.is_empty() {
+            return Err("bitmap font: no pages".to_string());
+        }
+        Ok(font)
+    }
+
+    fn add_entry(&mut self, tag: &str, attributes: &HashMap<&str, &str>) -> Result<(), String> {
+        let char_at = |key: &str| -> Result<char, String> {
+            char::from_u32(attribute(tag, attributes, key)?)
+                .ok_or(format!("bitmap font: invalid {key} in {tag}"))
+        };
+
+        match tag {
+            "common" => self.line_height = attribute(tag, attributes, "lineHeight")?,
+            "page" => {
+                let id: usize = attribute(tag, attributes, "id")?;
+                let file = attributes
+                    .get("file")
+                    .ok_or("bitmap font: page is missing file")?;
+                // Pages are numbered from 0, so ids beyond the next one are
+                // as good as invalid.
+                if id > self.pages.len() {
+                    return Err(format!("bitmap font: invalid id in {tag}"));
+                }
+                if id == self.pages.len() {
+                    self.pages.push(String::new());
+                }
+                self.pages[id] = file.to_string();
+            }
+            "char" => {
+                let glyph = Glyph {
+                    x: attribute(tag, attributes, "x")?,
+                    y: attribute(tag, attributes, "y")?,
+                    width: attribute(tag, attributes, "width")?,
+                    height: attribute(tag, attributes, "height")?,
+                    x_offset: attribute(tag, attributes, "xoffset")?,
+                    y_offset: attribute(tag, attributes, "yoffset")?,
+                    x_advance: attribute(tag, attributes, "xadvance")?,
+                    page: attribute(tag, attributes, "page").unwrap_or(0),
+                };
+                self.glyphs.insert(char_at("id")?, glyph);
+            }
+            "kerning" => {
+                let pair = (char_at("first")?, char_at("second")?);
+                self.kernings
+                    .insert(pair, attribute(tag, attributes, "amount")?);
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c)
+    }
+
+    pub fn text_width(&self, text: &str) -> u32 {
+        text.lines()
+            .map(|line| self.line_width(line))
+            .max()
+            .unwrap_or(0) as u32
+    }
+
+    pub fn layout(&self, text: &str, max_width: Option<u32>, align: TextAlign) -> Vec<PlacedGlyph> {
+        let lines = self.wrap(text, max_width);
+        let block_width = max_width.map(|w| w as i32);
+
+        let mut placed = vec![];
+        for (row, (start, line)) in lines.iter().enumerate() {
+            let width = self.line_width(line);
+            let mut x = match (align, block_width) {
+                (TextAlign::Left, _) => 0,
+                (TextAlign::Center, Some(block)) => (block - width) / 2,
+                (TextAlign::Center, None) => -width / 2,
+                (TextAlign::Right, Some(block)) => block - width,
+                (TextAlign::Right, None) => -width,
+            };
+            let y = row as i32 * self.line_height as i32;
+
+            let mut previous = None;
+            for (i, c) in line.char_indices() {
+                if let Some(glyph) = self.glyphs.get(&c) {
+                    x += self.kerning(previous, c);
+                    placed.push(PlacedGlyph {
+                        glyph: *glyph,
+                        x: x + glyph.x_offset,
+                        y: y + glyph.y_offset,
+                        index: text[..start + i].chars().count(),
+                    });
+                    x += glyph.x_advance;
+                }
+                previous = Some(c);
+            }
+        }
+        placed
+    }
+
+    fn kerning(&self, previous: Option<char>, c: char) -> i32 {
+        previous
+            .and_then(|p| self.kernings.get(&(p, c)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn line_width(&self, line: &str) -> i32 {
+        let mut previous = None;
+        let mut width = 0;
+        for c in line.chars() {
+            if let Some(glyph) = self.glyphs.get(&c) {
+                width += self.kerning(previous, c) + glyph.x_advance;
+            }
+            previous = Some(c);
+        }
+        width
+    }
+
+    // Splits text into lines at newlines and, if a maximum width is given, at
+    // the last space that keeps the line within it. Each line is returned with
+    // its byte offset into the original text.
+    fn wrap<'t>(&self, text: &'t str, max_width: Option<u32>) -> Vec<(usize, &'t str)> {
+        let mut lines = vec![];
+        let mut offset = 0;
+
+        for paragraph in text.split('\n') {
+            let mut start = 0;
+            let mut end = 0;
+
+            for (i, _) in paragraph.match_indices(' ').chain([(paragraph.len(), "")]) {
+                let too_wide =
+                    max_width.is_some_and(|max| self.line_width(&paragraph[start..i]) > max as i32);
+                if too_wide && end > start {
+                    lines.push((offset + start, &paragraph[start..end]));
+                    start = end + 1;
+                }
+                end = i;
+            }
+            lines.push((offset + start, &paragraph[start..]));
+            offset += paragraph.len() + 1;
+        }
+        lines
+    }
+}
+
+fn attribute<T: FromStr>(
+    tag: &str,
+    attributes: &HashMap<&str, &str>,
+    key: &str,
+) -> Result<T, String> {
+    attributes
+        .get(key)
+        .ok_or(format!("bitmap font: {tag} is missing {key}"))?
+        .parse()
+        .map_err(|_| format!("bitmap font: invalid {key} in {tag}"))
+}
+
+fn parse_fnt_line(line: &str) -> Option<(&str, HashMap<&str, &str>)> {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(char::is_whitespace)?;
+
+    let mut attributes = HashMap::new();
+    while let Some((key, value)) = rest.split_once('=') {
+        let value = value.trim_start();
+        let (value, remaining) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => value.split_once(char::is_whitespace).unwrap_or((value, "")),
+        };
+        attributes.insert(key.trim(), value);
+        rest = remaining;
+    }
+    Some((tag, attributes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT_FNT: &str = r#"info face="Test Font" size=8
+common lineHeight=10 base=8 scaleW=64 scaleH=64 pages=1
+page id=0 file="test.png"
+chars count=3
+char id=65 x=0 y=0 width=6 height=8 xoffset=0 yoffset=1 xadvance=7 page=0
+char id=66 x=6 y=0 width=6 height=8 xoffset=1 yoffset=1 xadvance=7 page=0
+char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=4 page=0
+kernings count=1
+kerning first=65 second=66 amount=-1
+"#;
+
+    const XML_FNT: &str = r#"<?xml version="1.0"?><font><info face="Test Font" size="8"/><common lineHeight="10" base="8" scaleW="64" scaleH="64" pages="1"/><pages><page id="0" file="test.png"/></pages><chars count="3"><char id="65" x="0" y="0" width="6" height="8" xoffset="0" yoffset="1" xadvance="7" page="0"/><char id="66" x="6" y="0" width="6" height="8" xoffset="1" yoffset="1" xadvance="7" page="0"/><char id="32" x="0" y="0" width="0" height="0" xoffset="0" yoffset="0" xadvance="4" page="0"/></chars><kernings count="1"><kerning first="65" second="66" amount="-1"/></kernings></font>"#;
+
+    fn grid_font() -> BitmapFont {
+        BitmapFont::grid("font.png", 8, 10, 16, "AB ")
+    }
+
+    fn positions(placed: &[PlacedGlyph]) -> Vec<(i32, i32, usize)> {
+        placed.iter().map(|p| (p.x, p.y, p.index)).collect()
+    }
+
+    fn check_parsed(font: &BitmapFont) {
+        assert_eq!(font.line_height, 10);
+        assert_eq!(font.pages, vec!["test.png".to_string()]);
+        let b = font.glyph('B').unwrap();
+        assert_eq!((b.x, b.width, b.x_offset, b.x_advance), (6, 6, 1, 7));
+        assert_eq!(font.glyph(' ').unwrap().x_advance, 4);
+        assert!(font.glyph('C').is_none());
+        assert_eq!(font.text_width("AB"), 13);
+    }
+
+    #[test]
+    fn parses_text_fnt() {
+        check_parsed(&BitmapFont::from_fnt(TEXT_FNT).unwrap());
+    }
+
+    #[test]
+    fn parses_single_line_xml_fnt() {
+        check_parsed(&BitmapFont::from_fnt(XML_FNT).unwrap());
+    }
+
+    #[test]
+    fn rejects_fnt_without_pages() {
+        assert!(BitmapFont::from_fnt("common lineHeight=10\n").is_err());
+        assert!(BitmapFont::from_fnt("<font><common lineHeight=\"10\"/></font>").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_page_ids() {
+        for id in ["-1", "1", "18446744073709551615", "x"] {
+            let source = format!("page id={id} file=\"test.png\"\n");
+            assert!(BitmapFont::from_fnt(&source).is_err(), "{id}");
+        }
+        let font = BitmapFont::from_fnt("page id=0 file=a.png\npage id=1 file=b.png\n").unwrap();
+        assert_eq!(font.pages, vec!["a.png".to_string(), "b.png".to_string()]);
+    }
+
+    #[test]
+    fn rejects_negative_sizes() {
+        let source = TEXT_FNT.replace("width=6 height=8 xoffset=1", "width=-6 height=8 xoffset=1");
+        assert!(BitmapFont::from_fnt(&source).is_err());
+    }
+
+    #[test]
+    fn layout_applies_offsets_and_kerning() {
+        let font = BitmapFont::from_fnt(TEXT_FNT).unwrap();
+        let placed = font.layout("AB", None, TextAlign::Left);
+        assert_eq!(positions(&placed), vec![(0, 1, 0), (7, 1, 1)]);
+    }
+
+    #[test]
+    fn layout_aligns_lines() {
+        let font = grid_font();
+        let center = font.layout("AB", Some(32), TextAlign::Center);
+        assert_eq!(positions(&center), vec![(8, 0, 0), (16, 0, 1)]);
+        let right = font.layout("AB", Some(32), TextAlign::Right);
+        assert_eq!(positions(&right), vec![(16, 0, 0), (24, 0, 1)]);
+        let right = font.layout("AB", None, TextAlign::Right);
+        assert_eq!(positions(&right), vec![(-16, 0, 0), (-8, 0, 1)]);
+        let center = font.layout("AB\nA", None, TextAlign::Center);
+        assert_eq!(positions(&center), vec![(-8, 0, 0), (0, 0, 1), (-4, 10, 3)]);
+    }
+
+    #[test]
+    fn wrap_breaks_at_spaces() {
+        let font = grid_font();
+        assert_eq!(font.wrap("AB AB", Some(24)), vec![(0, "AB"), (3, "AB")]);
+        assert_eq!(font.wrap("AB AB", Some(40)), vec![(0, "AB AB")]);
+        assert_eq!(font.wrap("AB AB", None), vec![(0, "AB AB")]);
+        assert_eq!(font.wrap("AB\nA", Some(8)), vec![(0, "AB"), (3, "A")]);
+    }
+
+    #[test]
+    fn glyph_indices_count_characters_of_the_text() {
+        let font = grid_font();
+        let placed = font.layout("AB AB", Some(24), TextAlign::Left);
+        let indices: Vec<usize> = placed.iter().map(|p| p.index).collect();
+        assert_eq!(indices, vec![0, 1, 3, 4]);
+        assert_eq!(positions(&placed)[2], (0, 10, 3));
+
+        // Characters without a glyph are skipped but still counted.
+        let placed = font.layout("AéB", None, TextAlign::Left);
+        let indices: Vec<usize> = placed.iter().map(|p| p.index).collect();
+        assert_eq!(indices, vec![0, 2]);
+    }
+}
@@ -1,17 +1,32 @@
-use std::{sync::Arc, thread};
+use std::{
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 
 use sdl2::{render::WindowCanvas, Sdl};
 
 use crate::{
-    audio::AudioModule, event::EventModule, window::WindowModule, GameHandle, SharedState,
-    WorldModule,
+    audio::AudioModule, event::EventModule, window::WindowModule, AudioBackend, GameHandle,
+    NullAudioBackend, ScaleMode, SdlAudioBackend, SharedState, WorldModule,
 };
 
+// How long the main loop waits when there was nothing new to draw.
+const IDLE_SLEEP: Duration = Duration::from_millis(1);
+
 pub struct Game {
     title: Option<String>,
     size: Option<(u32, u32)>,
+    logical_size: Option<(u32, u32)>,
+    scale_mode: ScaleMode,
     accelerated: bool,
     vsync: bool,
+    fps: Option<u32>,
+    software: bool,
+    headless: bool,
+    ticks_per_second: u16,
+    tick_limit: Option<u64>,
+    audio_backend: Option<Box<dyn AudioBackend>>,
 }
 
 macro_rules! uninitialized_fields {
@@ -33,26 +48,71 @@ impl Game {
         Game {
             title: None,
             size: None,
+            logical_size: None,
+            scale_mode: ScaleMode::Letterbox,
             accelerated: false,
             vsync: false,
+            fps: None,
+            software: false,
+            headless: false,
+            ticks_per_second: 20,
+            tick_limit: None,
+            audio_backend: None,
         }
     }
 
     pub fn start(&mut self, init: fn(&mut GameHandle)) -> Result<(), String> {
+        if self.headless && !self.software {
+            return self.start_headless(init);
+        }
+
         self.validate()?;
+        if self.headless {
+            sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+        }
         let sdl = sdl2::init()?;
         let canvas = self.make_canvas(&sdl)?;
         let texture_creator = canvas.texture_creator();
+        let ttf = sdl2::ttf::init().map_err(|e| e.to_string())?;
 
         let mut event_module = EventModule::new(sdl.event_pump()?);
-        let mut window_module = WindowModule::new(canvas, &texture_creator)?;
-        let mut audio_module = AudioModule::new();
+        let mut window_module = WindowModule::new(canvas, &texture_creator, &ttf)?;
+        if let Some((width, height)) = self.logical_size {
+            window_module.set_logical_size(width, height)?;
+        }
+        window_module.set_scale_mode(self.scale_mode)?;
+        let audio_backend = match self.audio_backend.take() {
+            Some(backend) => backend,
+            None if self.headless => Box::new(NullAudioBackend::new()),
+            None => Box::new(SdlAudioBackend::new(&sdl)?),
+        };
+        let mut audio_module = AudioModule::new(audio_backend);
         let state = Arc::new(SharedState::new());
 
+        // Each tick is rendered right after it on this thread, so the frames
+        // and screenshots don't depend on how fast the world runs, and the
+        // last tick is drawn too.
+        if self.headless {
+            window_module.set_interpolate(false);
+            let mut world = WorldModule::new(self.ticks_per_second);
+            world.set_tick_limit(self.tick_limit);
+            world.start(Arc::clone(&state), init);
+
+            while state.running() {
+                event_module.update(&state)?;
+                world.tick(Arc::clone(&state))?;
+                audio_module.update(&state)?;
+                window_module.render(&state)?;
+            }
+            return Ok(());
+        }
+
         let state2 = Arc::clone(&state);
+        let (ticks_per_second, tick_limit) = (self.ticks_per_second, self.tick_limit);
 
         let world_thread = thread::spawn(move || -> Result<(), String> {
-            let mut world = WorldModule::new(20);
+            let mut world = WorldModule::new(ticks_per_second);
+            world.set_tick_limit(tick_limit);
             world.start(Arc::clone(&state2), init);
 
             while state2.running() {
@@ -64,10 +124,20 @@ impl Game {
             Ok(())
         });
 
+        let frame_duration = self.fps.map(|fps| Duration::from_secs(1) / fps.max(1));
         while state.running() {
+            let frame_instant = Instant::now();
             event_module.update(&state)?;
             audio_module.update(&state)?;
-            window_module.update(&state)?;
+            let presented = window_module.update(&state)?;
+            match frame_duration {
+                Some(frame_duration) => {
+                    thread::sleep(frame_duration.saturating_sub(frame_instant.elapsed()))
+                }
+                // Nothing changed, so there is no need to poll again right away.
+                None if !presented => thread::sleep(IDLE_SLEEP),
+                None => (),
+            }
         }
 
         world_thread
@@ -76,6 +146,29 @@ impl Game {
         Ok(())
     }
 
+    // Runs the world on the current thread without SDL. Nothing is drawn, but
+    // the request and draw queues are still drained every tick.
+    fn start_headless(&mut self, init: fn(&mut GameHandle)) -> Result<(), String> {
+        let audio_backend = match self.audio_backend.take() {
+            Some(backend) => backend,
+            None => Box::new(NullAudioBackend::new()),
+        };
+        let mut audio_module = AudioModule::new(audio_backend);
+        let state = Arc::new(SharedState::new());
+
+        let mut world = WorldModule::new(self.ticks_per_second);
+        world.set_tick_limit(self.tick_limit);
+        world.start(Arc::clone(&state), init);
+
+        while state.running() {
+            world.tick(Arc::clone(&state))?;
+            audio_module.update(&state)?;
+            state.take_window_requests()?;
+            state.take_draw_data()?;
+        }
+        Ok(())
+    }
+
     fn validate(&self) -> Result<(), String> {
         let uninitialized = uninitialized_fields!(self, size, title);
 
@@ -97,6 +190,7 @@ impl Game {
             .build()
             .map_err(|e| e.to_string())?
             .into_canvas();
+        let c = if self.software { c.software() } else { c };
         let c = if self.vsync { c.present_vsync() } else { c };
         let c = if self.accelerated { c.accelerated() } else { c };
         c.build().map_err(|e| e.to_string())
@@ -112,6 +206,16 @@ impl Game {
         self
     }
 
+    pub fn logical_size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.logical_size = Some((width, height));
+        self
+    }
+
+    pub fn scale_mode(&mut self, mode: ScaleMode) -> &mut Self {
+        self.scale_mode = mode;
+        self
+    }
+
     pub fn accelerated(&mut self) -> &mut Self {
         self.accelerated = true;
         self
@@ -121,4 +225,94 @@ impl Game {
         self.vsync = true;
         self
     }
+
+    // Limits how often events are polled and frames are drawn by sleeping
+    // for the rest of each frame. Without it, and without vsync, the main
+    // loop runs as fast as it can while there are new frames to draw.
+    pub fn fps(&mut self, fps: u32) -> &mut Self {
+        self.fps = Some(fps);
+        self
+    }
+
+    pub fn software(&mut self) -> &mut Self {
+        self.software = true;
+        self
+    }
+
+    // Runs without a display or audio device, and without waiting between
+    // ticks. Together with `software` the frames are still rendered, to an
+    // invisible window of the given size, so screenshots can be taken. Every
+    // tick is then rendered, without interpolation, before the next one.
+    pub fn headless(&mut self) -> &mut Self {
+        self.headless = true;
+        self
+    }
+
+    // Can be changed while running with `WorldRequest::SetTicksPerSecond`.
+    pub fn ticks_per_second(&mut self, ticks: u16) -> &mut Self {
+        self.ticks_per_second = ticks;
+        self
+    }
+
+    // Stops the game after the given number of ticks, making `start` return.
+    pub fn tick_limit(&mut self, ticks: u64) -> &mut Self {
+        self.tick_limit = Some(ticks);
+        self
+    }
+
+    pub fn audio_backend<B: AudioBackend + 'static>(&mut self, backend: B) -> &mut Self {
+        self.audio_backend = Some(Box::new(backend));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use sdl2::{pixels::Color, rect::Rect};
+
+    use super::*;
+    use crate::{golden::SDL_LOCK, Delay, DrawData, Image, WindowRequest};
+
+    fn screenshot_path(tick: u64) -> String {
+        let path = env::temp_dir().join(format!("minigame-headless-{tick}.png"));
+        path.to_string_lossy().into_owned()
+    }
+
+    fn tick_color(tick: u64) -> Color {
+        Color::RGB(50 * (tick as u8 + 1), 0, 0)
+    }
+
+    #[test]
+    fn headless_software_games_render_every_tick() {
+        let _lock = SDL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for tick in 0..4 {
+            let _ = fs::remove_file(screenshot_path(tick));
+        }
+
+        Game::new()
+            .title("headless")
+            .size(4, 4)
+            .headless()
+            .software()
+            .tick_limit(3)
+            .start(|game| {
+                game.every(Delay::Ticks(1), |game| {
+                    game.draw(DrawData::FilledRectangle {
+                        rect: Rect::new(0, 0, 4, 4),
+                        color: tick_color(game.tick()),
+                    });
+                    let path = screenshot_path(game.tick());
+                    game.send(WindowRequest::Screenshot { path }).unwrap();
+                });
+            })
+            .unwrap();
+
+        for tick in 0..3 {
+            let image = Image::load(&screenshot_path(tick)).unwrap();
+            assert_eq!(image.pixel(0, 0), Some(tick_color(tick)));
+        }
+        assert!(Image::load(&screenshot_path(3)).is_err());
+    }
 }
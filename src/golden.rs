@@ -0,0 +1,293 @@
+use std::{env, path::Path, sync::Mutex};
+
+use sdl2::{
+    image::{LoadSurface, SaveSurface},
+    pixels::{Color, PixelFormatEnum},
+    surface::Surface,
+};
+
+use crate::{window::WindowModule, DrawCommand, DrawFrame, SharedState, WindowRequest};
+
+// An image read back from a canvas or loaded from a PNG. Pixels are stored
+// as RGBA bytes, row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Result<Self, String> {
+        if pixels.len() != (width * height * 4) as usize {
+            return Err(format!(
+                "image has {} bytes, expected {width}x{height} pixels",
+                pixels.len()
+            ));
+        }
+        Ok(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let surface = Surface::from_file(path)
+            .and_then(|surface| surface.convert_format(PixelFormatEnum::ABGR8888))
+            .map_err(|e| format!("{path}: {e}"))?;
+        let (width, height, pitch) = (surface.width(), surface.height(), surface.pitch());
+        let pixels = surface.with_lock(|data| {
+            (0..height as usize)
+                .flat_map(|y| {
+                    let row = y * pitch as usize;
+                    &data[row..row + width as usize * 4]
+                })
+                .copied()
+                .collect()
+        });
+        Image::new(width, height, pixels)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut pixels = self.pixels.clone();
+        let surface = Surface::from_data(
+            &mut pixels,
+            self.width,
+            self.height,
+            self.width * 4,
+            PixelFormatEnum::ABGR8888,
+        )?;
+        surface.save(path)
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = ((y * self.width + x) * 4) as usize;
+        let p = &self.pixels[i..i + 4];
+        Some(Color::RGBA(p[0], p[1], p[2], p[3]))
+    }
+
+    // Fails if the sizes differ or any channel of any pixel differs by more
+    // than the tolerance.
+    pub fn compare(&self, other: &Image, tolerance: u8) -> Result<(), String> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(format!(
+                "image size {}x{} differs from {}x{}",
+                self.width, self.height, other.width, other.height
+            ));
+        }
+
+        let mut differing = 0;
+        let mut max_difference = 0;
+        for (a, b) in self.pixels.chunks(4).zip(other.pixels.chunks(4)) {
+            let difference = a.iter().zip(b).map(|(a, b)| a.abs_diff(*b)).max().unwrap();
+            if difference > tolerance {
+                differing += 1;
+                max_difference = max_difference.max(difference);
+            }
+        }
+        if differing > 0 {
+            return Err(format!(
+                "{differing} pixels differ by up to {max_difference} (tolerance {tolerance})"
+            ));
+        }
+        Ok(())
+    }
+}
+
+// SDL can only be initialized on one thread at a time, and tests run on
+// several.
+pub(crate) static SDL_LOCK: Mutex<()> = Mutex::new(());
+
+// Renders a single frame with the software renderer of SDL's dummy video
+// driver, so no display or GPU is needed. The requests are handled first,
+// e.g. to load the textures and fonts used by the draw data.
+pub fn render_offscreen<D: Into<DrawCommand>>(
+    width: u32,
+    height: u32,
+    requests: Vec<WindowRequest>,
+    draw_data: impl IntoIterator<Item = D>,
+) -> Result<Image, String> {
+    with_offscreen_window(width, height, |window_module| {
+        let state = SharedState::new();
+        for request in requests {
+            state.send_window_request(request)?;
+        }
+        state.set_draw_data(DrawFrame {
+            commands: draw_data.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        })?;
+        window_module.render(&state)?;
+        window_module.read_pixels()
+    })
+}
+
+pub(crate) fn with_offscreen_window<T>(
+    width: u32,
+    height: u32,
+    f: impl FnOnce(&mut WindowModule) -> Result<T, String>,
+) -> Result<T, String> {
+    let _lock = SDL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+    let sdl = sdl2::init()?;
+    let canvas = sdl
+        .video()?
+        .window("", width, height)
+        .hidden()
+        .build()
+        .map_err(|e| e.to_string())?
+        .into_canvas()
+        .software()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let texture_creator = canvas.texture_creator();
+    let ttf = sdl2::ttf::init().map_err(|e| e.to_string())?;
+    let mut window_module = WindowModule::new(canvas, &texture_creator, &ttf)?;
+    f(&mut window_module)
+}
+
+// Compares the image with the PNG at the path. If the MINIGAME_UPDATE_GOLDEN
+// environment variable is set, the PNG is written instead.
+pub fn assert_golden(path: &str, image: &Image, tolerance: u8) -> Result<(), String> {
+    if env::var_os("MINIGAME_UPDATE_GOLDEN").is_some() {
+        return image.save(path);
+    }
+    if !Path::new(path).exists() {
+        return Err(format!(
+            "golden image not found: {path} (set MINIGAME_UPDATE_GOLDEN to create it)"
+        ));
+    }
+    image
+        .compare(&Image::load(path)?, tolerance)
+        .map_err(|e| format!("{path}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use sdl2::{rect::Rect, render::BlendMode};
+
+    use super::*;
+    use crate::DrawData;
+
+    fn filled(width: u32, height: u32, color: Color) -> Image {
+        let pixels = [color.r, color.g, color.b, color.a].repeat((width * height) as usize);
+        Image::new(width, height, pixels).unwrap()
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32, color: Color) -> DrawData {
+        DrawData::FilledRectangle {
+            rect: Rect::new(x, y, width, height),
+            color,
+        }
+    }
+
+    #[test]
+    fn new_checks_the_pixel_count() {
+        assert!(Image::new(2, 2, vec![0; 16]).is_ok());
+        assert!(Image::new(2, 2, vec![0; 12]).is_err());
+    }
+
+    #[test]
+    fn pixel_reads_rgba() {
+        let mut image = filled(2, 2, Color::BLACK);
+        image.pixels[12..16].copy_from_slice(&[1, 2, 3, 4]);
+        assert_eq!(image.pixel(1, 1), Some(Color::RGBA(1, 2, 3, 4)));
+        assert_eq!(image.pixel(0, 1), Some(Color::BLACK));
+        assert_eq!(image.pixel(2, 0), None);
+    }
+
+    #[test]
+    fn compare_identical_images() {
+        let image = filled(3, 2, Color::RGB(10, 20, 30));
+        assert_eq!(image.compare(&image.clone(), 0), Ok(()));
+    }
+
+    #[test]
+    fn compare_within_tolerance() {
+        let a = filled(3, 2, Color::RGB(10, 20, 30));
+        let mut b = a.clone();
+        b.pixels[4] = 13;
+        b.pixels[22] = 27;
+        assert_eq!(a.compare(&b, 3), Ok(()));
+        assert_eq!(b.compare(&a, 3), Ok(()));
+    }
+
+    #[test]
+    fn compare_beyond_tolerance() {
+        let a = filled(3, 2, Color::RGB(10, 20, 30));
+        let mut b = a.clone();
+        b.pixels[4] = 14;
+        b.pixels[11] = 0;
+        b.pixels[22] = 27;
+        assert_eq!(
+            a.compare(&b, 3),
+            Err("2 pixels differ by up to 255 (tolerance 3)".to_string())
+        );
+        assert!(a.compare(&b, 255).is_ok());
+    }
+
+    #[test]
+    fn compare_size_mismatch() {
+        let a = filled(3, 2, Color::BLACK);
+        let b = filled(2, 3, Color::BLACK);
+        assert_eq!(
+            a.compare(&b, 255),
+            Err("image size 3x2 differs from 2x3".to_string())
+        );
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = env::temp_dir().join("minigame-round-trip.png");
+        let path = path.to_string_lossy();
+        let mut image = filled(3, 2, Color::RGBA(10, 20, 30, 40));
+        image.pixels[0..4].copy_from_slice(&[255, 0, 128, 255]);
+        image.save(&path).unwrap();
+        assert_eq!(Image::load(&path).unwrap(), image);
+        assert!(Image::load("does/not/exist.png").is_err());
+    }
+
+    #[test]
+    fn golden_shapes() {
+        let requests = vec![
+            WindowRequest::SetBackgroundColor(Color::RGB(20, 20, 40)),
+            WindowRequest::CreateTarget {
+                id: "target".to_string(),
+                width: 8,
+                height: 8,
+            },
+        ];
+        let draw_data = vec![
+            rect(2, 2, 4, 4, Color::YELLOW).on_target("target"),
+            DrawData::Texture {
+                id: "target".to_string(),
+                src: None,
+                dst: Some(Rect::new(20, 12, 16, 16)),
+            }
+            .on_layer(1),
+            rect(4, 4, 16, 12, Color::RGBA(0, 0, 255, 128))
+                .on_layer(2)
+                .blend_mode(BlendMode::Blend),
+            rect(12, 8, 16, 12, Color::RED).on_layer(1),
+            DrawData::Rectangle {
+                rect: Rect::new(0, 0, 40, 30),
+                color: Color::WHITE,
+            }
+            .on_layer(3),
+            DrawData::Rectangle {
+                rect: Rect::new(2, 20, 8, 8),
+                color: Color::GREEN,
+            }
+            .on_screen(),
+        ];
+
+        let image = render_offscreen(40, 30, requests, draw_data).unwrap();
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/shapes.png");
+        assert_golden(path, &image, 2).unwrap();
+    }
+}
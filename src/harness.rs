@@ -0,0 +1,297 @@
+use std::{
+    marker::PhantomData,
+    mem,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use crate::{
+    AudioRequest, DrawData, DrawFrame, Entity, EventData, GameHandle, SharedState, WindowRequest,
+    WorldModule,
+};
+
+// Runs a world without a window, one tick at a time and without waiting
+// between ticks. Requests and draw data sent by the world are collected so
+// they can be inspected.
+pub struct TestHarness {
+    module: WorldModule,
+    state: Arc<SharedState>,
+    frame: DrawFrame,
+    window_requests: Vec<WindowRequest>,
+    audio_requests: Vec<(u64, AudioRequest)>,
+}
+
+impl TestHarness {
+    pub fn new(init: fn(&mut GameHandle)) -> Result<Self, String> {
+        let mut harness = TestHarness {
+            module: WorldModule::new(20),
+            state: Arc::new(SharedState::new()),
+            frame: DrawFrame::default(),
+            window_requests: vec![],
+            audio_requests: vec![],
+        };
+        harness.module.start(Arc::clone(&harness.state), init);
+        harness.collect()?;
+        Ok(harness)
+    }
+
+    pub fn tick(&mut self) -> Result<(), String> {
+        self.module.tick(Arc::clone(&self.state))?;
+        self.collect()
+    }
+
+    pub fn ticks(&mut self, ticks: u64) -> Result<(), String> {
+        for _ in 0..ticks {
+            self.tick()?;
+        }
+        Ok(())
+    }
+
+    fn collect(&mut self) -> Result<(), String> {
+        self.window_requests
+            .extend(self.state.take_window_requests()?);
+        self.audio_requests
+            .extend(self.state.take_audio_requests()?);
+        if let Some(frame) = self.state.take_draw_data()? {
+            self.frame = frame;
+        }
+        Ok(())
+    }
+
+    pub fn tick_count(&self) -> u64 {
+        self.state.tick()
+    }
+
+    pub fn running(&self) -> bool {
+        self.state.running()
+    }
+
+    // The event is seen by the systems during the next tick.
+    pub fn push_event(&self, event: EventData) -> Result<(), String> {
+        self.state.push_event(event)
+    }
+
+    // Gives access to the world between ticks, e.g. to add entities.
+    pub fn game(&mut self) -> HarnessGame<'_> {
+        HarnessGame {
+            game: self.module.game_handle(Arc::clone(&self.state)),
+            harness: PhantomData,
+        }
+    }
+
+    pub fn component<C: 'static>(&self, entity: Entity) -> Option<&C> {
+        self.module.world().component(entity)
+    }
+
+    pub fn components<C: 'static>(&self) -> impl Iterator<Item = (Entity, &C)> {
+        self.module.world().components()
+    }
+
+    pub fn resource<R: 'static>(&self) -> Option<&R> {
+        self.module.world().resource()
+    }
+
+    pub fn resource_mut<R: 'static>(&mut self) -> Option<&mut R> {
+        self.module.world_mut().resource_mut()
+    }
+
+    // The frame drawn during the last tick.
+    pub fn frame(&self) -> &DrawFrame {
+        &self.frame
+    }
+
+    pub fn draw_data(&self) -> impl Iterator<Item = &DrawData> {
+        self.frame.commands.iter().map(|command| &command.data)
+    }
+
+    // All requests sent since the harness was created or they were last
+    // taken.
+    pub fn window_requests(&self) -> &[WindowRequest] {
+        &self.window_requests
+    }
+
+    pub fn take_window_requests(&mut self) -> Vec<WindowRequest> {
+        mem::take(&mut self.window_requests)
+    }
+
+    pub fn audio_requests(&self) -> &[(u64, AudioRequest)] {
+        &self.audio_requests
+    }
+
+    pub fn take_audio_requests(&mut self) -> Vec<(u64, AudioRequest)> {
+        mem::take(&mut self.audio_requests)
+    }
+}
+
+// A game handle that borrows the harness mutably, so no references into the
+// world taken through the harness are alive while it changes the world.
+pub struct HarnessGame<'h> {
+    game: GameHandle,
+    harness: PhantomData<&'h mut TestHarness>,
+}
+
+impl Deref for HarnessGame<'_> {
+    type Target = GameHandle;
+
+    fn deref(&self) -> &GameHandle {
+        &self.game
+    }
+}
+
+impl DerefMut for HarnessGame<'_> {
+    fn deref_mut(&mut self) -> &mut GameHandle {
+        &mut self.game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::{mouse::MouseButton, pixels::Color, rect::Rect};
+
+    use super::*;
+
+    struct Counter(u32);
+
+    struct Clicks(Vec<(i32, i32)>);
+
+    struct Score(u32);
+
+    fn count(game: &mut GameHandle, _: Entity, counter: &mut Counter) {
+        counter.0 += 1;
+        if counter.0 == 3 {
+            let _ = game.send(WindowRequest::SetBackgroundColor(Color::RED));
+            let _ = game.send(AudioRequest::Play {
+                id: "beep".to_string(),
+                volume: 0.5,
+            });
+        }
+        game.draw(
+            DrawData::FilledRectangle {
+                rect: Rect::new(counter.0 as i32, 0, 1, 1),
+                color: Color::WHITE,
+            }
+            .on_layer(2),
+        );
+    }
+
+    fn click(game: &mut GameHandle, _: Entity, clicks: &mut Clicks) {
+        let new_clicks: Vec<_> = game
+            .events()
+            .iter()
+            .filter_map(|event| match event {
+                EventData::MouseButtonDown { x, y, .. } => Some((*x, *y)),
+                _ => None,
+            })
+            .collect();
+        if let Some(score) = game.resource_mut::<Score>() {
+            score.0 += new_clicks.len() as u32;
+        }
+        clicks.0.extend(new_clicks);
+    }
+
+    fn init(game: &mut GameHandle) {
+        game.insert_resource(Score(0));
+        game.add_entity()
+            .add_component(Counter(0))
+            .add_component(Clicks(vec![]));
+        game.add_system(count);
+        game.add_system(click);
+    }
+
+    fn entity(harness: &TestHarness) -> Entity {
+        harness.components::<Counter>().next().unwrap().0
+    }
+
+    #[test]
+    fn counts_ticks() {
+        let mut harness = TestHarness::new(init).unwrap();
+        assert_eq!(harness.tick_count(), 0);
+        assert_eq!(harness.component::<Counter>(entity(&harness)).unwrap().0, 0);
+
+        harness.ticks(3).unwrap();
+        assert_eq!(harness.tick_count(), 3);
+        assert_eq!(harness.component::<Counter>(entity(&harness)).unwrap().0, 3);
+        harness.tick().unwrap();
+        assert_eq!(harness.tick_count(), 4);
+
+        assert!(harness.running());
+        harness.game().stop();
+        assert!(!harness.running());
+    }
+
+    #[test]
+    fn delivers_events_on_the_next_tick() {
+        let mut harness = TestHarness::new(init).unwrap();
+        let click = EventData::MouseButtonDown {
+            button: MouseButton::Left,
+            x: 4,
+            y: 7,
+        };
+        harness.push_event(click.clone()).unwrap();
+        harness.push_event(click).unwrap();
+        let entity = entity(&harness);
+        assert!(harness.component::<Clicks>(entity).unwrap().0.is_empty());
+
+        harness.tick().unwrap();
+        assert_eq!(
+            harness.component::<Clicks>(entity).unwrap().0,
+            vec![(4, 7), (4, 7)]
+        );
+        assert_eq!(harness.resource::<Score>().unwrap().0, 2);
+
+        // Events are only seen during a single tick.
+        harness.tick().unwrap();
+        assert_eq!(harness.resource::<Score>().unwrap().0, 2);
+    }
+
+    #[test]
+    fn gives_access_to_the_world_between_ticks() {
+        let mut harness = TestHarness::new(init).unwrap();
+        harness.resource_mut::<Score>().unwrap().0 = 10;
+        let other = harness
+            .game()
+            .add_entity()
+            .add_component(Counter(5))
+            .entity();
+
+        harness.tick().unwrap();
+        assert_eq!(harness.resource::<Score>().unwrap().0, 10);
+        assert_eq!(harness.component::<Counter>(other).unwrap().0, 6);
+        assert_eq!(harness.components::<Counter>().count(), 2);
+        assert!(harness.component::<Clicks>(other).is_none());
+    }
+
+    #[test]
+    fn collects_requests_and_draw_data() {
+        let mut harness = TestHarness::new(init).unwrap();
+        assert_eq!(harness.draw_data().count(), 0);
+
+        harness.ticks(2).unwrap();
+        assert!(harness.window_requests().is_empty());
+        assert!(harness.audio_requests().is_empty());
+
+        harness.tick().unwrap();
+        assert!(matches!(
+            harness.window_requests(),
+            [WindowRequest::SetBackgroundColor(Color::RED)]
+        ));
+        assert!(matches!(
+            harness.audio_requests(),
+            [(2, AudioRequest::Play { id, .. })] if id == "beep"
+        ));
+
+        // Only the frame of the last tick is kept.
+        let draw_data: Vec<_> = harness.draw_data().collect();
+        assert!(matches!(
+            draw_data[..],
+            [DrawData::FilledRectangle { rect, .. }] if rect.x() == 3
+        ));
+        assert_eq!(harness.frame().commands[0].layer, 2);
+
+        assert_eq!(harness.take_window_requests().len(), 1);
+        assert_eq!(harness.take_audio_requests().len(), 1);
+        harness.tick().unwrap();
+        assert!(harness.window_requests().is_empty());
+        assert!(harness.audio_requests().is_empty());
+    }
+}
@@ -1,13 +1,36 @@
+mod animation;
+mod atlas;
 mod audio;
+mod camera;
 mod data;
 mod event;
+mod font;
 mod game;
+mod golden;
+mod harness;
+mod scene;
+mod schedule;
+mod synth;
+mod tilemap;
+mod timer;
+mod tween;
 mod window;
 mod world;
 
+pub use animation::*;
+pub use atlas::*;
 pub use audio::*;
+pub use camera::*;
 pub use data::*;
 pub use event::*;
+pub use font::*;
 pub use game::*;
+pub use golden::*;
+pub use harness::*;
+pub use scene::*;
+pub use synth::*;
+pub use tilemap::*;
+pub use timer::*;
+pub use tween::*;
 pub use window::*;
 pub use world::*;
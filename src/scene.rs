@@ -0,0 +1,160 @@
+use std::{any::TypeId, collections::HashMap, mem};
+
+use crate::{Entity, GameHandle, TimerId};
+
+// The entities, systems and timers a scene adds, from its enter hook or while
+// it's on top of the stack, are removed when it exits. Persistent entities
+// and the components and tweens of entities outside the scene are kept.
+#[derive(Clone, Copy)]
+pub struct Scene {
+    on_enter: fn(&mut GameHandle),
+    on_exit: Option<fn(&mut GameHandle)>,
+}
+
+impl Scene {
+    pub fn new(on_enter: fn(&mut GameHandle)) -> Self {
+        Scene {
+            on_enter,
+            on_exit: None,
+        }
+    }
+
+    pub fn on_exit(mut self, callback: fn(&mut GameHandle)) -> Self {
+        self.on_exit = Some(callback);
+        self
+    }
+}
+
+pub(crate) enum SceneChange {
+    Push(Scene),
+    Pop,
+    Switch(Scene),
+}
+
+// Entities, systems and timers added while a scene is on top of the stack
+// belong to it and are removed when it exits. As scenes exit in reverse
+// order, the systems of a scene are always the last ones of each storage.
+pub(crate) struct ActiveScene {
+    scene: Scene,
+    pub(crate) entities: Vec<Entity>,
+    pub(crate) system_counts: HashMap<TypeId, usize>,
+    pub(crate) timers: Vec<TimerId>,
+}
+
+#[derive(Default)]
+pub(crate) struct Scenes {
+    pub(crate) stack: Vec<ActiveScene>,
+    changes: Vec<SceneChange>,
+}
+
+impl Scenes {
+    pub(crate) fn change(&mut self, change: SceneChange) {
+        self.changes.push(change);
+    }
+}
+
+// Runs at the end of every tick and after init, outside of any system, so
+// the storages can be changed safely.
+pub(crate) fn apply_scene_changes(game: &mut GameHandle) {
+    loop {
+        let changes = mem::take(&mut game.scenes().changes);
+        if changes.is_empty() {
+            return;
+        }
+        for change in changes {
+            match change {
+                SceneChange::Push(scene) => enter(game, scene),
+                SceneChange::Pop => exit(game),
+                SceneChange::Switch(scene) => {
+                    exit(game);
+                    enter(game, scene);
+                }
+            }
+        }
+    }
+}
+
+fn enter(game: &mut GameHandle, scene: Scene) {
+    let system_counts = game.system_counts();
+    game.scenes().stack.push(ActiveScene {
+        scene,
+        entities: vec![],
+        system_counts,
+        timers: vec![],
+    });
+    (scene.on_enter)(game);
+}
+
+fn exit(game: &mut GameHandle) {
+    let scene = match game.scenes().stack.last() {
+        Some(active) => active.scene,
+        None => return,
+    };
+    if let Some(on_exit) = scene.on_exit {
+        on_exit(game);
+    }
+    if let Some(active) = game.scenes().stack.pop() {
+        game.remove_scene(active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Delay, TestHarness};
+
+    struct Marker;
+
+    #[derive(Default)]
+    struct Counts {
+        fired: u32,
+        exited: u32,
+    }
+
+    fn fire(game: &mut GameHandle) {
+        game.resource_mut::<Counts>().unwrap().fired += 1;
+    }
+
+    fn enter_level(game: &mut GameHandle) {
+        game.add_entity().add_component(Marker);
+        game.add_persistent_entity().add_component(Marker);
+        game.every(Delay::Ticks(1), fire);
+    }
+
+    fn exit_level(game: &mut GameHandle) {
+        game.resource_mut::<Counts>().unwrap().exited += 1;
+    }
+
+    fn init(game: &mut GameHandle) {
+        game.insert_resource(Counts::default());
+        game.push_scene(Scene::new(enter_level).on_exit(exit_level));
+    }
+
+    #[test]
+    fn exiting_removes_entities_and_cancels_timers() {
+        let mut harness = TestHarness::new(init).unwrap();
+        assert_eq!(harness.components::<Marker>().count(), 2);
+        harness.tick().unwrap();
+        assert_eq!(harness.resource::<Counts>().unwrap().fired, 1);
+
+        // The scene exits at the end of the next tick.
+        harness.game().pop_scene();
+        harness.ticks(3).unwrap();
+        let counts = harness.resource::<Counts>().unwrap();
+        assert_eq!((counts.fired, counts.exited), (2, 1));
+        assert_eq!(harness.components::<Marker>().count(), 1);
+    }
+
+    #[test]
+    fn switching_replaces_the_scene() {
+        let mut harness = TestHarness::new(init).unwrap();
+        harness.game().switch_scene(Scene::new(enter_level));
+        harness.tick().unwrap();
+        assert_eq!(harness.resource::<Counts>().unwrap().exited, 1);
+        assert_eq!(harness.components::<Marker>().count(), 3);
+
+        // Only the timer of the new scene is left.
+        harness.ticks(2).unwrap();
+        assert_eq!(harness.resource::<Counts>().unwrap().fired, 3);
+    }
+}
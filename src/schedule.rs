@@ -0,0 +1,56 @@
+use std::mem;
+
+use crate::GameHandle;
+
+pub(crate) trait ScheduleId: Copy + PartialEq {
+    fn new(id: u64) -> Self;
+}
+
+// Timers and tweens that advance once per unpaused tick, after the systems,
+// until they finish or are cancelled.
+pub(crate) struct Schedule<I, T> {
+    next_id: u64,
+    items: Vec<(I, T)>,
+    cancelled: Vec<I>,
+}
+
+impl<I, T> Default for Schedule<I, T> {
+    fn default() -> Self {
+        Schedule {
+            next_id: 0,
+            items: vec![],
+            cancelled: vec![],
+        }
+    }
+}
+
+impl<I: ScheduleId, T> Schedule<I, T> {
+    pub(crate) fn add(&mut self, item: T) -> I {
+        let id = I::new(self.next_id);
+        self.next_id += 1;
+        self.items.push((id, item));
+        id
+    }
+
+    pub(crate) fn cancel(&mut self, id: I) {
+        self.cancelled.push(id);
+    }
+}
+
+// Advances every item that wasn't cancelled with `step`, which returns
+// whether the item keeps running. As the items are taken out of the game
+// meanwhile, `step` can add and cancel items through it.
+pub(crate) fn run<I: ScheduleId, T>(
+    game: &mut GameHandle,
+    schedule: fn(&mut GameHandle) -> &mut Schedule<I, T>,
+    mut step: impl FnMut(&mut GameHandle, I, &mut T) -> bool,
+) {
+    let mut items = mem::take(&mut schedule(game).items);
+    items.retain_mut(|(id, item)| !schedule(game).cancelled.contains(id) && step(game, *id, item));
+
+    let schedule = schedule(game);
+    items.append(&mut schedule.items);
+    let cancelled = mem::take(&mut schedule.cancelled);
+    items.retain(|(id, _)| !cancelled.contains(id));
+    schedule.items = items;
+}
@@ -0,0 +1,186 @@
+use std::f32::consts::TAU;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Square,
+    Sawtooth,
+    Sine,
+    Noise,
+}
+
+#[derive(Clone, Debug)]
+pub struct SfxrParams {
+    pub waveform: Waveform,
+    // Envelope stage lengths are in seconds.
+    pub attack: f32,
+    pub sustain: f32,
+    pub sustain_punch: f32,
+    pub decay: f32,
+    // Frequencies are in Hz, slides in octaves per second (and per second squared).
+    pub frequency: f32,
+    pub min_frequency: f32,
+    pub slide: f32,
+    pub delta_slide: f32,
+    // Vibrato depth is a fraction of the frequency, speed is in Hz.
+    pub vibrato_depth: f32,
+    pub vibrato_speed: f32,
+    // Duty cycle of the square wave, sweep in duty per second.
+    pub duty: f32,
+    pub duty_sweep: f32,
+    pub volume: f32,
+    pub seed: u64,
+}
+
+impl Default for SfxrParams {
+    fn default() -> Self {
+        SfxrParams {
+            waveform: Waveform::Square,
+            attack: 0.0,
+            sustain: 0.1,
+            sustain_punch: 0.0,
+            decay: 0.1,
+            frequency: 440.0,
+            min_frequency: 20.0,
+            slide: 0.0,
+            delta_slide: 0.0,
+            vibrato_depth: 0.0,
+            vibrato_speed: 0.0,
+            duty: 0.5,
+            duty_sweep: 0.0,
+            volume: 0.5,
+            seed: 1,
+        }
+    }
+}
+
+impl SfxrParams {
+    pub fn blip() -> Self {
+        SfxrParams {
+            sustain: 0.05,
+            decay: 0.05,
+            frequency: 880.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn pickup() -> Self {
+        SfxrParams {
+            sustain: 0.04,
+            sustain_punch: 0.4,
+            decay: 0.15,
+            frequency: 1000.0,
+            slide: 2.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn laser() -> Self {
+        SfxrParams {
+            waveform: Waveform::Sawtooth,
+            sustain: 0.05,
+            decay: 0.15,
+            frequency: 1500.0,
+            min_frequency: 200.0,
+            slide: -8.0,
+            duty: 0.3,
+            duty_sweep: 1.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn explosion() -> Self {
+        SfxrParams {
+            waveform: Waveform::Noise,
+            sustain: 0.1,
+            sustain_punch: 0.6,
+            decay: 0.5,
+            frequency: 120.0,
+            min_frequency: 20.0,
+            slide: -1.5,
+            vibrato_depth: 0.2,
+            vibrato_speed: 12.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn hit() -> Self {
+        SfxrParams {
+            waveform: Waveform::Noise,
+            sustain: 0.02,
+            decay: 0.12,
+            frequency: 400.0,
+            slide: -4.0,
+            ..Default::default()
+        }
+    }
+
+    pub fn render(&self, sample_rate: u32) -> Vec<f32> {
+        let dt = 1.0 / sample_rate as f32;
+        let length = self.attack.max(0.0) + self.sustain.max(0.0) + self.decay.max(0.0);
+        let sample_count = (length * sample_rate as f32) as usize;
+
+        let mut samples = Vec::with_capacity(sample_count);
+        let mut rng = XorShift::new(self.seed);
+        let mut noise = rng.next_sample();
+        let mut frequency = self.frequency;
+        let mut slide = self.slide;
+        let mut duty = self.duty;
+        let mut phase = 0.0f32;
+
+        for i in 0..sample_count {
+            let t = i as f32 * dt;
+
+            slide += self.delta_slide * dt;
+            frequency *= (slide * dt).exp2();
+            if frequency < self.min_frequency {
+                break;
+            }
+            duty = (duty + self.duty_sweep * dt).clamp(0.0, 1.0);
+
+            let vibrato = 1.0 + (TAU * self.vibrato_speed * t).sin() * self.vibrato_depth;
+            phase += frequency * vibrato * dt;
+            if phase >= 1.0 {
+                phase = phase.fract();
+                noise = rng.next_sample();
+            }
+
+            let sample = match self.waveform {
+                Waveform::Square if phase < duty => 1.0,
+                Waveform::Square => -1.0,
+                Waveform::Sawtooth => 1.0 - phase * 2.0,
+                Waveform::Sine => (TAU * phase).sin(),
+                Waveform::Noise => noise,
+            };
+            samples.push(sample * self.envelope(t) * self.volume);
+        }
+        samples
+    }
+
+    fn envelope(&self, t: f32) -> f32 {
+        if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.sustain {
+            let t = (t - self.attack) / self.sustain;
+            1.0 + (1.0 - t) * 2.0 * self.sustain_punch
+        } else if self.decay > 0.0 {
+            1.0 - (t - self.attack - self.sustain) / self.decay
+        } else {
+            0.0
+        }
+    }
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        XorShift(seed.max(1))
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+}
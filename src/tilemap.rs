@@ -0,0 +1,760 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use roxmltree::{Document, Node};
+use sdl2::rect::Rect;
+use serde_json::Value;
+
+use crate::{EntityHandle, GameHandle};
+
+const FLIPPED_HORIZONTALLY: u32 = 0x80000000;
+const FLIPPED_VERTICALLY: u32 = 0x40000000;
+const FLIPPED_DIAGONALLY: u32 = 0x20000000;
+const FLAGS: u32 = 0xf0000000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+pub type Properties = HashMap<String, PropertyValue>;
+
+#[derive(Clone, Debug)]
+pub struct Tileset {
+    pub first_gid: u32,
+    pub name: String,
+    pub image: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    pub margin: u32,
+    pub spacing: u32,
+}
+
+impl Tileset {
+    pub fn tile_rect(&self, gid: u32) -> Rect {
+        let local = (gid & !FLAGS) - self.first_gid;
+        let column = local % self.columns.max(1);
+        let row = local / self.columns.max(1);
+        Rect::new(
+            (self.margin + column * (self.tile_width + self.spacing)) as i32,
+            (self.margin + row * (self.tile_height + self.spacing)) as i32,
+            self.tile_width,
+            self.tile_height,
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TileLayer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub visible: bool,
+    pub opacity: f32,
+    pub tiles: Vec<u32>,
+    pub properties: Properties,
+}
+
+impl TileLayer {
+    // Returns the global tile id at the position, with flip flags cleared.
+    pub fn tile(&self, x: u32, y: u32) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        match self.tiles[(y * self.width + x) as usize] & !FLAGS {
+            0 => None,
+            gid => Some(gid),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MapObject {
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub rotation: f32,
+    pub gid: Option<u32>,
+    pub visible: bool,
+    pub properties: Properties,
+}
+
+#[derive(Clone, Debug)]
+pub struct ObjectLayer {
+    pub name: String,
+    pub visible: bool,
+    pub objects: Vec<MapObject>,
+    pub properties: Properties,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tilemap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<Tileset>,
+    pub layers: Vec<TileLayer>,
+    pub object_layers: Vec<ObjectLayer>,
+    pub properties: Properties,
+}
+
+impl Tilemap {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("tmx") => Self::from_tmx(&source, dir),
+            Some("tmj") | Some("json") => Self::from_tmj(&source, dir),
+            _ => Err(format!("tilemap: unknown format: {path}")),
+        }
+        .map_err(|e| format!("{path}: {e}"))
+    }
+
+    // Paths to tileset images and external tilesets are resolved relative to
+    // `dir`.
+    pub fn from_tmx(source: &str, dir: &Path) -> Result<Self, String> {
+        let document = Document::parse(source).map_err(|e| e.to_string())?;
+        let map = document.root_element();
+        check_orientation(map.attribute("orientation"))?;
+
+        let mut tilemap = Tilemap {
+            width: xml_attribute(map, "width")?,
+            height: xml_attribute(map, "height")?,
+            tile_width: xml_attribute(map, "tilewidth")?,
+            tile_height: xml_attribute(map, "tileheight")?,
+            tilesets: vec![],
+            layers: vec![],
+            object_layers: vec![],
+            properties: xml_properties(map)?,
+        };
+
+        for node in map.children().filter(Node::is_element) {
+            if node.has_tag_name("tileset") {
+                let first_gid = xml_attribute(node, "firstgid")?;
+                let tileset = match node.attribute("source") {
+                    Some(source) => load_external_tileset(first_gid, &dir.join(source))?,
+                    None => xml_tileset(first_gid, node, dir)?,
+                };
+                tilemap.tilesets.push(tileset);
+            }
+        }
+        xml_layers(&mut tilemap, map)?;
+        tilemap.tilesets.sort_by_key(|tileset| tileset.first_gid);
+        Ok(tilemap)
+    }
+
+    pub fn from_tmj(source: &str, dir: &Path) -> Result<Self, String> {
+        let map: Value = serde_json::from_str(source).map_err(|e| e.to_string())?;
+        check_orientation(map["orientation"].as_str())?;
+
+        let mut tilemap = Tilemap {
+            width: json_u32(&map, "width")?,
+            height: json_u32(&map, "height")?,
+            tile_width: json_u32(&map, "tilewidth")?,
+            tile_height: json_u32(&map, "tileheight")?,
+            tilesets: vec![],
+            layers: vec![],
+            object_layers: vec![],
+            properties: json_properties(&map)?,
+        };
+
+        for tileset in map["tilesets"].as_array().into_iter().flatten() {
+            let first_gid = json_u32(tileset, "firstgid")?;
+            let tileset = match tileset["source"].as_str() {
+                Some(source) => load_external_tileset(first_gid, &dir.join(source))?,
+                None => json_tileset(first_gid, tileset, dir)?,
+            };
+            tilemap.tilesets.push(tileset);
+        }
+        json_layers(&mut tilemap, &map)?;
+        tilemap.tilesets.sort_by_key(|tileset| tileset.first_gid);
+        Ok(tilemap)
+    }
+
+    pub fn tileset(&self, gid: u32) -> Option<&Tileset> {
+        let gid = gid & !FLAGS;
+        self.tilesets
+            .iter()
+            .rev()
+            .find(|tileset| tileset.first_gid <= gid)
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&TileLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn object_layer(&self, name: &str) -> Option<&ObjectLayer> {
+        self.object_layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = &MapObject> {
+        self.object_layers
+            .iter()
+            .flat_map(|layer| layer.objects.iter())
+    }
+
+    // Adds an entity for every object and lets `spawn` attach its components.
+    pub fn spawn_objects(&self, game: &mut GameHandle, spawn: fn(&mut EntityHandle, &MapObject)) {
+        for object in self.objects() {
+            spawn(&mut game.add_entity(), object);
+        }
+    }
+}
+
+// Returns the rotation in degrees and the horizontal and vertical flips that
+// reproduce the flip flags of a global tile id with `copy_ex`.
+pub fn tile_orientation(gid: u32) -> (f64, bool, bool) {
+    let h = gid & FLIPPED_HORIZONTALLY != 0;
+    let v = gid & FLIPPED_VERTICALLY != 0;
+    if gid & FLIPPED_DIAGONALLY != 0 {
+        (90.0, v, !h)
+    } else {
+        (0.0, h, v)
+    }
+}
+
+fn check_orientation(orientation: Option<&str>) -> Result<(), String> {
+    match orientation {
+        None | Some("orthogonal") => Ok(()),
+        Some(orientation) => Err(format!("unsupported orientation: {orientation}")),
+    }
+}
+
+fn load_external_tileset(first_gid: u32, path: &Path) -> Result<Tileset, String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("tsx") => {
+            let document = Document::parse(&source).map_err(|e| e.to_string())?;
+            xml_tileset(first_gid, document.root_element(), dir)
+        }
+        _ => {
+            let tileset: Value = serde_json::from_str(&source).map_err(|e| e.to_string())?;
+            json_tileset(first_gid, &tileset, dir)
+        }
+    }
+}
+
+fn xml_attribute<T: std::str::FromStr>(node: Node, name: &str) -> Result<T, String> {
+    node.attribute(name)
+        .ok_or(format!("{} is missing {name}", node.tag_name().name()))?
+        .parse()
+        .map_err(|_| format!("invalid {name} in {}", node.tag_name().name()))
+}
+
+fn xml_attribute_or<T: std::str::FromStr>(node: Node, name: &str, default: T) -> Result<T, String> {
+    match node.attribute(name) {
+        Some(_) => xml_attribute(node, name),
+        None => Ok(default),
+    }
+}
+
+fn xml_properties(node: Node) -> Result<Properties, String> {
+    let mut properties = Properties::new();
+    let nodes = node
+        .children()
+        .filter(|n| n.has_tag_name("properties"))
+        .flat_map(|n| n.children())
+        .filter(|n| n.has_tag_name("property"));
+
+    for property in nodes {
+        let name: String = xml_attribute(property, "name")?;
+        let value = property
+            .attribute("value")
+            .or(property.text())
+            .unwrap_or("");
+        let value = parse_property(property.attribute("type"), value)
+            .ok_or(format!("invalid value for property {name}"))?;
+        properties.insert(name, value);
+    }
+    Ok(properties)
+}
+
+fn parse_property(kind: Option<&str>, value: &str) -> Option<PropertyValue> {
+    Some(match kind {
+        Some("bool") => PropertyValue::Bool(value.parse().ok()?),
+        Some("int") | Some("object") => PropertyValue::Int(value.parse().ok()?),
+        Some("float") => PropertyValue::Float(value.parse().ok()?),
+        _ => PropertyValue::String(value.to_string()),
+    })
+}
+
+fn xml_tileset(first_gid: u32, node: Node, dir: &Path) -> Result<Tileset, String> {
+    let image = node
+        .children()
+        .find(|n| n.has_tag_name("image"))
+        .ok_or("tileset is missing image")?;
+    let source: String = xml_attribute(image, "source")?;
+
+    Ok(Tileset {
+        first_gid,
+        name: xml_attribute_or(node, "name", String::new())?,
+        image: dir.join(source).to_string_lossy().into_owned(),
+        tile_width: xml_attribute(node, "tilewidth")?,
+        tile_height: xml_attribute(node, "tileheight")?,
+        columns: xml_attribute(node, "columns")?,
+        tile_count: xml_attribute(node, "tilecount")?,
+        margin: xml_attribute_or(node, "margin", 0)?,
+        spacing: xml_attribute_or(node, "spacing", 0)?,
+    })
+}
+
+fn xml_layers(tilemap: &mut Tilemap, parent: Node) -> Result<(), String> {
+    for node in parent.children().filter(Node::is_element) {
+        match node.tag_name().name() {
+            "layer" => tilemap.layers.push(xml_tile_layer(node)?),
+            "objectgroup" => tilemap.object_layers.push(xml_object_layer(node)?),
+            "group" => xml_layers(tilemap, node)?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn xml_tile_layer(node: Node) -> Result<TileLayer, String> {
+    let width: u32 = xml_attribute(node, "width")?;
+    let height: u32 = xml_attribute(node, "height")?;
+    let data = node
+        .children()
+        .find(|n| n.has_tag_name("data"))
+        .ok_or("layer is missing data")?;
+    if data.attribute("compression").is_some() {
+        return Err("compressed layer data is not supported".to_string());
+    }
+
+    let tiles = match data.attribute("encoding") {
+        Some("csv") => parse_csv(data.text().unwrap_or(""))?,
+        Some("base64") => parse_base64_tiles(data.text().unwrap_or(""))?,
+        Some(encoding) => return Err(format!("unsupported encoding: {encoding}")),
+        None => data
+            .children()
+            .filter(|n| n.has_tag_name("tile"))
+            .map(|n| xml_attribute_or(n, "gid", 0))
+            .collect::<Result<_, _>>()?,
+    };
+    if tiles.len() != (width * height) as usize {
+        return Err("layer data doesn't match its size".to_string());
+    }
+
+    Ok(TileLayer {
+        name: xml_attribute_or(node, "name", String::new())?,
+        width,
+        height,
+        visible: xml_attribute_or(node, "visible", 1)? != 0,
+        opacity: xml_attribute_or(node, "opacity", 1.0)?,
+        tiles,
+        properties: xml_properties(node)?,
+    })
+}
+
+fn xml_object_layer(node: Node) -> Result<ObjectLayer, String> {
+    let objects = node
+        .children()
+        .filter(|n| n.has_tag_name("object"))
+        .map(|object| {
+            let class = object.attribute("class").or(object.attribute("type"));
+            Ok(MapObject {
+                id: xml_attribute_or(object, "id", 0)?,
+                name: xml_attribute_or(object, "name", String::new())?,
+                class: class.unwrap_or("").to_string(),
+                x: xml_attribute(object, "x")?,
+                y: xml_attribute(object, "y")?,
+                width: xml_attribute_or(object, "width", 0.0)?,
+                height: xml_attribute_or(object, "height", 0.0)?,
+                rotation: xml_attribute_or(object, "rotation", 0.0)?,
+                gid: object
+                    .attribute("gid")
+                    .map(|_| xml_attribute(object, "gid"))
+                    .transpose()?,
+                visible: xml_attribute_or(object, "visible", 1)? != 0,
+                properties: xml_properties(object)?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(ObjectLayer {
+        name: xml_attribute_or(node, "name", String::new())?,
+        visible: xml_attribute_or(node, "visible", 1)? != 0,
+        objects,
+        properties: xml_properties(node)?,
+    })
+}
+
+fn json_u32(value: &Value, key: &str) -> Result<u32, String> {
+    value[key]
+        .as_u64()
+        .map(|v| v as u32)
+        .ok_or(format!("missing {key}"))
+}
+
+fn json_f32(value: &Value, key: &str) -> f32 {
+    value[key].as_f64().unwrap_or(0.0) as f32
+}
+
+fn json_str<'v>(value: &'v Value, key: &str) -> &'v str {
+    value[key].as_str().unwrap_or("")
+}
+
+fn json_properties(value: &Value) -> Result<Properties, String> {
+    let mut properties = Properties::new();
+    for property in value["properties"].as_array().into_iter().flatten() {
+        let name = json_str(property, "name").to_string();
+        let value = match &property["value"] {
+            Value::Bool(value) => PropertyValue::Bool(*value),
+            Value::Number(value) if value.is_i64() => PropertyValue::Int(value.as_i64().unwrap()),
+            Value::Number(value) => PropertyValue::Float(value.as_f64().unwrap_or(0.0)),
+            Value::String(value) => PropertyValue::String(value.clone()),
+            _ => return Err(format!("invalid value for property {name}")),
+        };
+        properties.insert(name, value);
+    }
+    Ok(properties)
+}
+
+fn json_tileset(first_gid: u32, tileset: &Value, dir: &Path) -> Result<Tileset, String> {
+    let image = tileset["image"]
+        .as_str()
+        .ok_or("tileset is missing image")?;
+    Ok(Tileset {
+        first_gid,
+        name: json_str(tileset, "name").to_string(),
+        image: dir.join(image).to_string_lossy().into_owned(),
+        tile_width: json_u32(tileset, "tilewidth")?,
+        tile_height: json_u32(tileset, "tileheight")?,
+        columns: json_u32(tileset, "columns")?,
+        tile_count: json_u32(tileset, "tilecount")?,
+        margin: json_u32(tileset, "margin").unwrap_or(0),
+        spacing: json_u32(tileset, "spacing").unwrap_or(0),
+    })
+}
+
+fn json_layers(tilemap: &mut Tilemap, parent: &Value) -> Result<(), String> {
+    for layer in parent["layers"].as_array().into_iter().flatten() {
+        let name = json_str(layer, "name").to_string();
+        let visible = layer["visible"].as_bool().unwrap_or(true);
+
+        match json_str(layer, "type") {
+            "tilelayer" => {
+                if layer["compression"].as_str().is_some_and(|c| !c.is_empty()) {
+                    return Err("compressed layer data is not supported".to_string());
+                }
+                let (width, height) = (json_u32(layer, "width")?, json_u32(layer, "height")?);
+                let tiles = match &layer["data"] {
+                    Value::Array(data) => data
+                        .iter()
+                        .map(|gid| gid.as_u64().map(|gid| gid as u32))
+                        .collect::<Option<_>>()
+                        .ok_or("invalid layer data")?,
+                    Value::String(data) => parse_base64_tiles(data)?,
+                    _ => return Err("layer is missing data".to_string()),
+                };
+                if tiles.len() != (width * height) as usize {
+                    return Err("layer data doesn't match its size".to_string());
+                }
+                tilemap.layers.push(TileLayer {
+                    name,
+                    width,
+                    height,
+                    visible,
+                    opacity: layer["opacity"].as_f64().unwrap_or(1.0) as f32,
+                    tiles,
+                    properties: json_properties(layer)?,
+                });
+            }
+            "objectgroup" => {
+                let objects = layer["objects"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|object| {
+                        let class = object["class"].as_str().or(object["type"].as_str());
+                        Ok(MapObject {
+                            id: json_u32(object, "id").unwrap_or(0),
+                            name: json_str(object, "name").to_string(),
+                            class: class.unwrap_or("").to_string(),
+                            x: json_f32(object, "x"),
+                            y: json_f32(object, "y"),
+                            width: json_f32(object, "width"),
+                            height: json_f32(object, "height"),
+                            rotation: json_f32(object, "rotation"),
+                            gid: json_u32(object, "gid").ok(),
+                            visible: object["visible"].as_bool().unwrap_or(true),
+                            properties: json_properties(object)?,
+                        })
+                    })
+                    .collect::<Result<_, String>>()?;
+                tilemap.object_layers.push(ObjectLayer {
+                    name,
+                    visible,
+                    objects,
+                    properties: json_properties(layer)?,
+                });
+            }
+            "group" => json_layers(tilemap, layer)?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn parse_csv(data: &str) -> Result<Vec<u32>, String> {
+    data.split(',')
+        .map(|gid| {
+            gid.trim()
+                .parse()
+                .map_err(|_| "invalid layer data".to_string())
+        })
+        .collect()
+}
+
+fn parse_base64_tiles(data: &str) -> Result<Vec<u32>, String> {
+    let bytes = decode_base64(data)?;
+    if bytes.len() % 4 != 0 {
+        return Err("invalid layer data".to_string());
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+        .collect())
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in data
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err("invalid base64 data".to_string()),
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16">
+ <properties>
+  <property name="music" value="forest.ogg"/>
+ </properties>
+ <tileset firstgid="1" name="tiles" tilewidth="16" tileheight="16" tilecount="8" columns="4" margin="1" spacing="2">
+  <image source="tiles.png" width="74" height="38"/>
+ </tileset>
+ <tileset firstgid="9" name="trees" tilewidth="16" tileheight="32" tilecount="2" columns="2">
+  <image source="trees.png" width="32" height="32"/>
+ </tileset>
+ <layer id="1" name="ground" width="2" height="2">
+  <data encoding="csv">
+1,2,
+2147483651,0
+</data>
+ </layer>
+ <group name="decoration">
+  <layer id="2" name="trees" width="2" height="2" visible="0" opacity="0.5">
+   <properties>
+    <property name="solid" type="bool" value="true"/>
+   </properties>
+   <data encoding="base64">
+    AQAAAAIAAAADAACAAAAAAA==
+   </data>
+  </layer>
+ </group>
+ <layer id="3" name="plain" width="2" height="1">
+  <data>
+   <tile gid="9"/>
+   <tile/>
+  </data>
+ </layer>
+ <objectgroup id="4" name="spawns">
+  <object id="1" name="player" type="spawn" x="8" y="24.5">
+   <properties>
+    <property name="health" type="int" value="3"/>
+    <property name="speed" type="float" value="1.5"/>
+   </properties>
+  </object>
+  <object id="2" gid="10" x="16" y="32" width="16" height="32" visible="0"/>
+ </objectgroup>
+</map>
+"#;
+
+    const TMJ: &str = r#"{
+        "orientation": "orthogonal",
+        "width": 2, "height": 2, "tilewidth": 16, "tileheight": 16,
+        "properties": [{"name": "music", "type": "string", "value": "forest.ogg"}],
+        "tilesets": [
+            {"firstgid": 9, "name": "trees", "image": "trees.png", "tilewidth": 16,
+             "tileheight": 32, "tilecount": 2, "columns": 2},
+            {"firstgid": 1, "name": "tiles", "image": "tiles.png", "tilewidth": 16,
+             "tileheight": 16, "tilecount": 8, "columns": 4, "margin": 1, "spacing": 2}
+        ],
+        "layers": [
+            {"type": "tilelayer", "name": "ground", "width": 2, "height": 2,
+             "data": [1, 2, 2147483651, 0]},
+            {"type": "group", "name": "decoration", "layers": [
+                {"type": "tilelayer", "name": "trees", "width": 2, "height": 2,
+                 "visible": false, "opacity": 0.5, "encoding": "base64",
+                 "data": "AQAAAAIAAAADAACAAAAAAA==",
+                 "properties": [{"name": "solid", "type": "bool", "value": true}]}
+            ]},
+            {"type": "objectgroup", "name": "spawns", "objects": [
+                {"id": 1, "name": "player", "type": "spawn", "x": 8, "y": 24.5,
+                 "properties": [
+                    {"name": "health", "type": "int", "value": 3},
+                    {"name": "speed", "type": "float", "value": 1.5}
+                 ]},
+                {"id": 2, "gid": 10, "x": 16, "y": 32, "width": 16, "height": 32,
+                 "visible": false}
+            ]}
+        ]
+    }"#;
+
+    fn check_map(map: &Tilemap) {
+        assert_eq!((map.width, map.height), (2, 2));
+        assert_eq!((map.tile_width, map.tile_height), (16, 16));
+        assert_eq!(
+            map.properties.get("music"),
+            Some(&PropertyValue::String("forest.ogg".to_string()))
+        );
+
+        let first_gids: Vec<u32> = map.tilesets.iter().map(|t| t.first_gid).collect();
+        assert_eq!(first_gids, vec![1, 9]);
+        assert_eq!(
+            map.tilesets[0].image,
+            Path::new("maps").join("tiles.png").to_str().unwrap()
+        );
+        assert_eq!(map.tileset(8).unwrap().name, "tiles");
+        assert_eq!(map.tileset(10).unwrap().name, "trees");
+        assert_eq!(map.tileset(0x80000003).unwrap().name, "tiles");
+
+        let ground = map.layer("ground").unwrap();
+        assert_eq!(ground.tiles, vec![1, 2, 0x80000003, 0]);
+        assert_eq!(ground.tile(0, 1), Some(3));
+        assert_eq!(ground.tile(1, 1), None);
+        assert_eq!(ground.tile(2, 0), None);
+        assert!(ground.visible);
+
+        let trees = map.layer("trees").unwrap();
+        assert_eq!(trees.tiles, ground.tiles);
+        assert!(!trees.visible);
+        assert_eq!(trees.opacity, 0.5);
+        assert_eq!(
+            trees.properties.get("solid"),
+            Some(&PropertyValue::Bool(true))
+        );
+
+        let spawns = map.object_layer("spawns").unwrap();
+        let player = &spawns.objects[0];
+        assert_eq!(
+            (player.name.as_str(), player.class.as_str()),
+            ("player", "spawn")
+        );
+        assert_eq!((player.x, player.y, player.gid), (8.0, 24.5, None));
+        assert_eq!(
+            player.properties.get("health"),
+            Some(&PropertyValue::Int(3))
+        );
+        assert_eq!(
+            player.properties.get("speed"),
+            Some(&PropertyValue::Float(1.5))
+        );
+        let tree = &spawns.objects[1];
+        assert_eq!(
+            (tree.gid, tree.height, tree.visible),
+            (Some(10), 32.0, false)
+        );
+        assert_eq!(map.objects().count(), 2);
+    }
+
+    #[test]
+    fn parses_tmx() {
+        let map = Tilemap::from_tmx(TMX, Path::new("maps")).unwrap();
+        check_map(&map);
+        assert_eq!(map.layer("plain").unwrap().tiles, vec![9, 0]);
+    }
+
+    #[test]
+    fn parses_tmj() {
+        check_map(&Tilemap::from_tmj(TMJ, Path::new("maps")).unwrap());
+    }
+
+    #[test]
+    fn rejects_unsupported_maps() {
+        let isometric = TMX.replace("orthogonal", "isometric");
+        assert!(Tilemap::from_tmx(&isometric, Path::new("")).is_err());
+        let compressed = TMX.replace(
+            "encoding=\"base64\"",
+            "encoding=\"base64\" compression=\"zlib\"",
+        );
+        assert!(Tilemap::from_tmx(&compressed, Path::new("")).is_err());
+        let short = TMX.replace("2147483651,0", "2147483651");
+        assert!(Tilemap::from_tmx(&short, Path::new("")).is_err());
+    }
+
+    #[test]
+    fn decodes_layer_data() {
+        assert_eq!(parse_csv("1, 2,\n3").unwrap(), vec![1, 2, 3]);
+        assert!(parse_csv("1,x").is_err());
+        assert_eq!(decode_base64("TWFu").unwrap(), b"Man");
+        assert_eq!(decode_base64("TW\nE=").unwrap(), b"Ma");
+        assert!(decode_base64("TW*u").is_err());
+        assert_eq!(
+            parse_base64_tiles("AQAAAAIAAAADAACAAAAAAA==").unwrap(),
+            vec![1, 2, 0x80000003, 0]
+        );
+        assert!(parse_base64_tiles("TWFu").is_err());
+    }
+
+    #[test]
+    fn tile_rect_skips_margin_and_spacing() {
+        let map = Tilemap::from_tmx(TMX, Path::new("")).unwrap();
+        let tiles = &map.tilesets[0];
+        assert_eq!(tiles.tile_rect(1), Rect::new(1, 1, 16, 16));
+        assert_eq!(tiles.tile_rect(6), Rect::new(19, 19, 16, 16));
+        assert_eq!(
+            tiles.tile_rect(6 | FLIPPED_VERTICALLY),
+            Rect::new(19, 19, 16, 16)
+        );
+        assert_eq!(map.tilesets[1].tile_rect(10), Rect::new(16, 0, 16, 32));
+    }
+
+    #[test]
+    fn flip_flags_map_to_rotation_and_flips() {
+        let h = FLIPPED_HORIZONTALLY;
+        let v = FLIPPED_VERTICALLY;
+        let d = FLIPPED_DIAGONALLY;
+        assert_eq!(tile_orientation(5), (0.0, false, false));
+        assert_eq!(tile_orientation(5 | h), (0.0, true, false));
+        assert_eq!(tile_orientation(5 | v), (0.0, false, true));
+        assert_eq!(tile_orientation(5 | h | v), (0.0, true, true));
+        assert_eq!(tile_orientation(5 | d), (90.0, false, true));
+        assert_eq!(tile_orientation(5 | d | h), (90.0, false, false));
+        assert_eq!(tile_orientation(5 | d | v), (90.0, true, true));
+        assert_eq!(tile_orientation(5 | d | h | v), (90.0, true, false));
+    }
+}
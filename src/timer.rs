@@ -0,0 +1,253 @@
+use std::time::Duration;
+
+use crate::{
+    schedule::{self, Schedule, ScheduleId},
+    Entity, EventData, GameHandle,
+};
+
+// Tick delays count unpaused ticks whatever the time scale, so they keep
+// running at a time scale of 0. Time delays count game time, which follows it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Delay {
+    Ticks(u64),
+    Time(Duration),
+}
+
+impl Delay {
+    // Negative and NaN seconds count as no delay.
+    pub fn seconds(seconds: f32) -> Self {
+        let seconds = seconds.max(0.0);
+        Delay::Time(Duration::try_from_secs_f32(seconds).unwrap_or(Duration::MAX))
+    }
+}
+
+impl From<Duration> for Delay {
+    fn from(duration: Duration) -> Self {
+        Delay::Time(duration)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+// Counts unpaused ticks and game time. Only the latter follows the time scale,
+// and neither advances while the world is paused.
+#[derive(Clone, Copy, Debug)]
+struct Countdown {
+    delay: Delay,
+    repeat: bool,
+    ticks: u64,
+    elapsed: Duration,
+    finished: bool,
+}
+
+impl Countdown {
+    fn new(delay: Delay, repeat: bool) -> Self {
+        Countdown {
+            delay,
+            repeat,
+            ticks: 0,
+            elapsed: Duration::ZERO,
+            finished: false,
+        }
+    }
+
+    // Returns true if the delay passed during this step. Repeating countdowns
+    // fire at most once per step and keep the overshoot.
+    fn advance(&mut self, delta: Duration) -> bool {
+        if self.finished {
+            return false;
+        }
+
+        self.ticks += 1;
+        self.elapsed += delta;
+        let due = match self.delay {
+            Delay::Ticks(ticks) => self.ticks >= ticks,
+            Delay::Time(time) => self.elapsed >= time,
+        };
+        if !due {
+            return false;
+        }
+
+        if !self.repeat {
+            self.finished = true;
+        }
+        match self.delay {
+            Delay::Ticks(ticks) => self.ticks -= ticks.min(self.ticks),
+            Delay::Time(time) => self.elapsed -= time.min(self.elapsed),
+        }
+        true
+    }
+
+    fn progress(&self) -> f32 {
+        if self.finished {
+            return 1.0;
+        }
+        match self.delay {
+            Delay::Ticks(0) => 1.0,
+            Delay::Ticks(ticks) => self.ticks as f32 / ticks as f32,
+            Delay::Time(time) if time.is_zero() => 1.0,
+            Delay::Time(time) => self.elapsed.as_secs_f32() / time.as_secs_f32(),
+        }
+        .min(1.0)
+    }
+}
+
+pub struct Timer {
+    pub paused: bool,
+    countdown: Countdown,
+    on_fire: Option<fn(&mut GameHandle, Entity)>,
+}
+
+impl Timer {
+    pub fn once<D: Into<Delay>>(delay: D) -> Self {
+        Timer::new(delay.into(), false)
+    }
+
+    pub fn repeating<D: Into<Delay>>(interval: D) -> Self {
+        Timer::new(interval.into(), true)
+    }
+
+    fn new(delay: Delay, repeat: bool) -> Self {
+        Timer {
+            paused: false,
+            countdown: Countdown::new(delay, repeat),
+            on_fire: None,
+        }
+    }
+
+    pub fn on_fire(mut self, callback: fn(&mut GameHandle, Entity)) -> Self {
+        self.on_fire = Some(callback);
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.countdown.finished
+    }
+
+    // How far the timer is towards firing next, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.countdown.progress()
+    }
+
+    pub fn reset(&mut self) {
+        self.countdown = Countdown::new(self.countdown.delay, self.countdown.repeat);
+    }
+}
+
+pub(crate) fn update_timer(game: &mut GameHandle, entity: Entity, timer: &mut Timer) {
+    if timer.paused || !timer.countdown.advance(game.delta_duration()) {
+        return;
+    }
+    if let Some(callback) = timer.on_fire {
+        callback(game, entity);
+    }
+    let _ = game.push_event(EventData::EntityTimerFired { entity });
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum TimerAction {
+    Callback(fn(&mut GameHandle)),
+    Event,
+}
+
+pub(crate) struct ScheduledTimer {
+    countdown: Countdown,
+    action: TimerAction,
+}
+
+impl ScheduleId for TimerId {
+    fn new(id: u64) -> Self {
+        TimerId(id)
+    }
+}
+
+pub(crate) type Timers = Schedule<TimerId, ScheduledTimer>;
+
+impl Timers {
+    pub(crate) fn schedule(&mut self, delay: Delay, repeat: bool, action: TimerAction) -> TimerId {
+        self.add(ScheduledTimer {
+            countdown: Countdown::new(delay, repeat),
+            action,
+        })
+    }
+}
+
+pub(crate) fn run_timers(game: &mut GameHandle) {
+    let delta = game.delta_duration();
+    schedule::run(game, GameHandle::timers, |game, id, timer| {
+        if timer.countdown.advance(delta) {
+            match timer.action {
+                TimerAction::Callback(callback) => callback(game),
+                TimerAction::Event => {
+                    let _ = game.push_event(EventData::TimerFired { id });
+                }
+            }
+        }
+        !timer.countdown.finished
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestHarness;
+
+    #[derive(Default)]
+    struct Seen(Vec<EventData>);
+
+    fn record(game: &mut GameHandle, _: Entity, seen: &mut Seen) {
+        seen.0.extend(game.events().iter().cloned());
+    }
+
+    fn seen(harness: &TestHarness) -> &[EventData] {
+        &harness.components::<Seen>().next().unwrap().1 .0
+    }
+
+    #[test]
+    fn seconds_are_clamped() {
+        assert_eq!(Delay::seconds(0.5), Delay::Time(Duration::from_millis(500)));
+        assert_eq!(Delay::seconds(-1.0), Delay::Time(Duration::ZERO));
+        assert_eq!(Delay::seconds(f32::NAN), Delay::Time(Duration::ZERO));
+        assert_eq!(Delay::seconds(f32::INFINITY), Delay::Time(Duration::MAX));
+    }
+
+    #[test]
+    fn scheduled_and_entity_timers_push_events() {
+        let mut harness = TestHarness::new(|game| {
+            game.add_entity()
+                .add_component(Seen::default())
+                .add_component(Timer::once(Delay::Ticks(2)));
+            game.add_system(record);
+            game.event_after(Delay::Ticks(1));
+        })
+        .unwrap();
+
+        // Events pushed during a tick are seen during the next one.
+        harness.ticks(2).unwrap();
+        assert!(matches!(seen(&harness), [EventData::TimerFired { .. }]));
+        harness.ticks(2).unwrap();
+        assert!(matches!(
+            seen(&harness),
+            [
+                EventData::TimerFired { .. },
+                EventData::EntityTimerFired { .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn only_time_delays_follow_the_time_scale() {
+        let mut harness = TestHarness::new(|game| {
+            game.add_entity().add_component(Seen::default());
+            game.add_system(record);
+            game.set_time_scale(0.0);
+            game.event_after(Delay::Ticks(1));
+            game.event_after(Delay::seconds(0.01));
+        })
+        .unwrap();
+
+        harness.ticks(3).unwrap();
+        assert_eq!(seen(&harness).len(), 1);
+    }
+}
@@ -0,0 +1,513 @@
+use std::{f32::consts::PI, time::Duration};
+
+use sdl2::pixels::Color;
+
+use crate::{
+    schedule::{self, Schedule, ScheduleId},
+    Entity, EventData, GameHandle,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+}
+
+impl Easing {
+    // Maps the progress t, from 0 to 1, onto the curve. Elastic and back
+    // curves overshoot outside of 0 to 1 in between.
+    pub fn apply(self, t: f32) -> f32 {
+        const BACK: f32 = 1.70158;
+        const BACK_IN_OUT: f32 = BACK * 1.525;
+        const ELASTIC: f32 = 2.0 * PI / 3.0;
+        const ELASTIC_IN_OUT: f32 = 2.0 * PI / 4.5;
+
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Easing::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Easing::QuadInOut => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut if t < 0.5 => 4.0 * t.powi(3),
+            Easing::CubicInOut => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            _ if t == 0.0 || t == 1.0 => t,
+            Easing::ElasticIn => {
+                -(2.0f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * ELASTIC).sin()
+            }
+            Easing::ElasticOut => {
+                2.0f32.powf(-10.0 * t) * ((10.0 * t - 0.75) * ELASTIC).sin() + 1.0
+            }
+            Easing::ElasticInOut if t < 0.5 => {
+                -(2.0f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * ELASTIC_IN_OUT).sin()) / 2.0
+            }
+            Easing::ElasticInOut => {
+                2.0f32.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * ELASTIC_IN_OUT).sin() / 2.0
+                    + 1.0
+            }
+            Easing::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut if t < 0.5 => (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0,
+            Easing::BounceInOut => (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0,
+            Easing::BackIn => (BACK + 1.0) * t.powi(3) - BACK * t * t,
+            Easing::BackOut => 1.0 + (BACK + 1.0) * (t - 1.0).powi(3) + BACK * (t - 1.0).powi(2),
+            Easing::BackInOut if t < 0.5 => {
+                (2.0 * t).powi(2) * ((BACK_IN_OUT + 1.0) * 2.0 * t - BACK_IN_OUT) / 2.0
+            }
+            Easing::BackInOut => {
+                ((2.0 * t - 2.0).powi(2) * ((BACK_IN_OUT + 1.0) * (2.0 * t - 2.0) + BACK_IN_OUT)
+                    + 2.0)
+                    / 2.0
+            }
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        N * (t - 1.5 / D).powi(2) + 0.75
+    } else if t < 2.5 / D {
+        N * (t - 2.25 / D).powi(2) + 0.9375
+    } else {
+        N * (t - 2.625 / D).powi(2) + 0.984375
+    }
+}
+
+pub trait Tweenable: Copy {
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Tweenable for f64 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t as f64
+    }
+}
+
+impl Tweenable for i32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        (*self as f32).lerp(&(*other as f32), t).round() as i32
+    }
+}
+
+impl Tweenable for u8 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        (*self as f32)
+            .lerp(&(*other as f32), t)
+            .round()
+            .clamp(0.0, 255.0) as u8
+    }
+}
+
+impl Tweenable for (f32, f32) {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        (self.0.lerp(&other.0, t), self.1.lerp(&other.1, t))
+    }
+}
+
+impl Tweenable for Color {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Color::RGBA(
+            self.r.lerp(&other.r, t),
+            self.g.lerp(&other.g, t),
+            self.b.lerp(&other.b, t),
+            self.a.lerp(&other.a, t),
+        )
+    }
+}
+
+trait Track<C> {
+    fn apply(&mut self, component: &mut C, t: f32);
+}
+
+// Starts from the value the field has when the track starts, unless a start
+// value was given.
+struct FieldTrack<C, V> {
+    field: fn(&mut C) -> &mut V,
+    from: Option<V>,
+    to: V,
+}
+
+impl<C, V: Tweenable> Track<C> for FieldTrack<C, V> {
+    fn apply(&mut self, component: &mut C, t: f32) {
+        let field = (self.field)(component);
+        let from = *self.from.get_or_insert(*field);
+        *field = from.lerp(&self.to, t);
+    }
+}
+
+enum TweenKind<C> {
+    Track {
+        track: Box<dyn Track<C>>,
+        duration: Duration,
+        easing: Easing,
+        elapsed: Duration,
+    },
+    Wait {
+        duration: Duration,
+        elapsed: Duration,
+    },
+    Sequence {
+        tweens: Vec<Tween<C>>,
+        current: usize,
+    },
+    Parallel {
+        tweens: Vec<(Tween<C>, Option<Duration>)>,
+    },
+}
+
+pub struct Tween<C> {
+    kind: TweenKind<C>,
+    on_finish: Option<fn(&mut GameHandle, Entity)>,
+}
+
+impl<C: 'static> Tween<C> {
+    // Tweens the field from its current value to the given one.
+    pub fn to<V: Tweenable + 'static>(
+        field: fn(&mut C) -> &mut V,
+        to: V,
+        duration: Duration,
+        easing: Easing,
+    ) -> Self {
+        Tween::track(
+            FieldTrack {
+                field,
+                from: None,
+                to,
+            },
+            duration,
+            easing,
+        )
+    }
+
+    pub fn from_to<V: Tweenable + 'static>(
+        field: fn(&mut C) -> &mut V,
+        from: V,
+        to: V,
+        duration: Duration,
+        easing: Easing,
+    ) -> Self {
+        Tween::track(
+            FieldTrack {
+                field,
+                from: Some(from),
+                to,
+            },
+            duration,
+            easing,
+        )
+    }
+
+    fn track<T: Track<C> + 'static>(track: T, duration: Duration, easing: Easing) -> Self {
+        Tween::new(TweenKind::Track {
+            track: Box::new(track),
+            duration,
+            easing,
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    pub fn wait(duration: Duration) -> Self {
+        Tween::new(TweenKind::Wait {
+            duration,
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    pub fn sequence(tweens: Vec<Tween<C>>) -> Self {
+        Tween::new(TweenKind::Sequence { tweens, current: 0 })
+    }
+
+    pub fn parallel(tweens: Vec<Tween<C>>) -> Self {
+        let tweens = tweens.into_iter().map(|tween| (tween, None)).collect();
+        Tween::new(TweenKind::Parallel { tweens })
+    }
+
+    fn new(kind: TweenKind<C>) -> Self {
+        Tween {
+            kind,
+            on_finish: None,
+        }
+    }
+
+    pub fn then(self, next: Tween<C>) -> Self {
+        match self.kind {
+            TweenKind::Sequence {
+                mut tweens,
+                current,
+            } if self.on_finish.is_none() => {
+                tweens.push(next);
+                Tween::new(TweenKind::Sequence { tweens, current })
+            }
+            _ => Tween::sequence(vec![self, next]),
+        }
+    }
+
+    pub fn on_finish(mut self, callback: fn(&mut GameHandle, Entity)) -> Self {
+        self.on_finish = Some(callback);
+        self
+    }
+
+    // Returns the time left over if the tween finished during this step.
+    // Finish callbacks of nested tweens are collected to be called once the
+    // component is no longer borrowed.
+    fn advance(
+        &mut self,
+        component: &mut C,
+        delta: Duration,
+        finished: &mut Vec<fn(&mut GameHandle, Entity)>,
+    ) -> Option<Duration> {
+        let left = match &mut self.kind {
+            TweenKind::Track {
+                track,
+                duration,
+                easing,
+                elapsed,
+            } => {
+                *elapsed += delta;
+                let t = if duration.is_zero() {
+                    1.0
+                } else {
+                    elapsed.as_secs_f32() / duration.as_secs_f32()
+                };
+                track.apply(component, easing.apply(t.min(1.0)));
+                elapsed.checked_sub(*duration)
+            }
+            TweenKind::Wait { duration, elapsed } => {
+                *elapsed += delta;
+                elapsed.checked_sub(*duration)
+            }
+            TweenKind::Sequence { tweens, current } => {
+                let mut delta = Some(delta);
+                while let (Some(left), Some(tween)) = (delta, tweens.get_mut(*current)) {
+                    delta = tween.advance(component, left, finished);
+                    if delta.is_some() {
+                        *current += 1;
+                    }
+                }
+                delta
+            }
+            TweenKind::Parallel { tweens } => {
+                for (tween, left) in tweens.iter_mut().filter(|(_, left)| left.is_none()) {
+                    *left = tween.advance(component, delta, finished);
+                }
+                tweens
+                    .iter()
+                    .map(|(_, left)| *left)
+                    .try_fold(delta, |min, left| Some(min.min(left?)))
+            }
+        };
+        if left.is_some() {
+            finished.extend(self.on_finish);
+        }
+        left
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TweenId(u64);
+
+pub(crate) trait ActiveTween {
+    // Returns whether the tween finished, or None if its component is gone.
+    fn advance(&mut self, game: &mut GameHandle, entity: Entity, delta: Duration) -> Option<bool>;
+}
+
+impl<C: 'static> ActiveTween for Tween<C> {
+    fn advance(&mut self, game: &mut GameHandle, entity: Entity, delta: Duration) -> Option<bool> {
+        let mut finished = vec![];
+        let component = game.component_mut::<C>(entity)?;
+        let done = Tween::advance(self, component, delta, &mut finished).is_some();
+        for callback in finished {
+            callback(game, entity);
+        }
+        Some(done)
+    }
+}
+
+impl ScheduleId for TweenId {
+    fn new(id: u64) -> Self {
+        TweenId(id)
+    }
+}
+
+pub(crate) type Tweens = Schedule<TweenId, (Entity, Box<dyn ActiveTween>)>;
+
+impl Tweens {
+    pub(crate) fn start<C: 'static>(&mut self, entity: Entity, tween: Tween<C>) -> TweenId {
+        self.add((entity, Box::new(tween)))
+    }
+}
+
+pub(crate) fn run_tweens(game: &mut GameHandle) {
+    let delta = game.delta_duration();
+    schedule::run(
+        game,
+        GameHandle::tweens,
+        |game, id, (entity, tween)| match tween.advance(game, *entity, delta) {
+            Some(false) => true,
+            Some(true) => {
+                let _ = game.push_event(EventData::TweenFinished {
+                    entity: *entity,
+                    id,
+                });
+                false
+            }
+            None => false,
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestHarness;
+
+    const EASINGS: [Easing; 16] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::ElasticInOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::BounceInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+    ];
+
+    #[derive(Default)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    fn x(position: &mut Position) -> &mut f32 {
+        &mut position.x
+    }
+
+    fn y(position: &mut Position) -> &mut f32 {
+        &mut position.y
+    }
+
+    fn done(_: &mut GameHandle, _: Entity) {}
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn easings_start_at_0_and_end_at_1() {
+        for easing in EASINGS {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{easing:?} at 0");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{easing:?} at 1");
+            assert_eq!(easing.apply(-1.0), easing.apply(0.0), "{easing:?} below 0");
+            assert_eq!(easing.apply(2.0), easing.apply(1.0), "{easing:?} above 1");
+        }
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert_eq!(Easing::QuadIn.apply(0.5), 0.25);
+        assert!(Easing::BackIn.apply(0.2) < 0.0);
+        assert!(Easing::ElasticOut.apply(0.2) > 1.0);
+    }
+
+    #[test]
+    fn sequence_finishes_after_its_last_tween() {
+        let mut tween = Tween::sequence(vec![
+            Tween::to(x, 10.0, ms(100), Easing::Linear),
+            Tween::wait(ms(50)),
+        ])
+        .then(Tween::from_to(y, 2.0, 6.0, ms(100), Easing::Linear).on_finish(done));
+        let mut position = Position::default();
+        let mut finished = vec![];
+
+        assert_eq!(tween.advance(&mut position, ms(50), &mut finished), None);
+        assert_eq!(position.x, 5.0);
+        assert_eq!(tween.advance(&mut position, ms(50), &mut finished), None);
+        assert_eq!(position.x, 10.0);
+
+        // The time left after the wait goes to the next tween.
+        assert_eq!(tween.advance(&mut position, ms(75), &mut finished), None);
+        assert_eq!(position.y, 3.0);
+        assert!(finished.is_empty());
+        assert_eq!(
+            tween.advance(&mut position, ms(100), &mut finished),
+            Some(ms(25))
+        );
+        assert_eq!((position.x, position.y), (10.0, 6.0));
+        assert_eq!(finished.len(), 1);
+    }
+
+    #[test]
+    fn parallel_finishes_after_its_longest_tween() {
+        let mut tween = Tween::parallel(vec![
+            Tween::to(x, 10.0, ms(100), Easing::Linear).on_finish(done),
+            Tween::to(y, 4.0, ms(200), Easing::Linear),
+        ])
+        .on_finish(done);
+        let mut position = Position::default();
+        let mut finished = vec![];
+
+        assert_eq!(tween.advance(&mut position, ms(150), &mut finished), None);
+        assert_eq!((position.x, position.y), (10.0, 3.0));
+        assert_eq!(finished.len(), 1);
+        assert_eq!(
+            tween.advance(&mut position, ms(100), &mut finished),
+            Some(ms(50))
+        );
+        assert_eq!((position.x, position.y), (10.0, 4.0));
+        assert_eq!(finished.len(), 2);
+    }
+
+    #[test]
+    fn tweens_run_until_finished_or_cancelled() {
+        let mut harness = TestHarness::new(|game| {
+            game.add_entity().add_component(Position::default());
+        })
+        .unwrap();
+        let entity = harness.components::<Position>().next().unwrap().0;
+        let mut game = harness.game();
+        game.tween(entity, Tween::to(x, 10.0, ms(100), Easing::Linear));
+        let cancelled = game.tween(entity, Tween::to(y, 10.0, ms(100), Easing::Linear));
+
+        harness.tick().unwrap();
+        let position = harness.component::<Position>(entity).unwrap();
+        assert_eq!((position.x, position.y), (5.0, 5.0));
+
+        harness.game().cancel_tween(cancelled);
+        harness.ticks(2).unwrap();
+        let position = harness.component::<Position>(entity).unwrap();
+        assert_eq!((position.x, position.y), (10.0, 5.0));
+    }
+}
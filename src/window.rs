@@ -1,59 +1,313 @@
-use std::{collections::HashMap, rc::Rc, time::Instant};
+use std::{collections::HashMap, fs, mem, ptr, time::Instant};
 
 use sdl2::{
     gfx::primitives::DrawRenderer,
     image::LoadTexture,
-    pixels::Color,
-    render::{Texture, TextureCreator, WindowCanvas},
+    pixels::{Color, PixelFormatEnum},
+    rect::Rect,
+    render::{BlendMode, Texture, TextureCreator, WindowCanvas},
+    ttf::{Font, Sdl2TtfContext},
     video::{FullscreenType, WindowContext},
 };
 
-use crate::{DrawData, SharedState, WindowRequest};
+use crate::{
+    tile_orientation, Atlas, BitmapFont, Camera, DrawData, DrawFrame, DrawSpace, DrawStyle, Image,
+    ScaleMode, SharedState, TileLayer, Tilemap, WindowRequest,
+};
 
 pub struct WindowModule<'a> {
     canvas: WindowCanvas,
     texture_creator: &'a TextureCreator<WindowContext>,
-    texture_cache: HashMap<&'a str, Rc<Texture<'a>>>,
+    texture_cache: HashMap<String, Texture<'a>>,
+    sprites: HashMap<String, (String, Rect)>,
+    bitmap_fonts: HashMap<String, BitmapFont>,
+    tilemaps: HashMap<String, CachedTilemap>,
+    ttf: &'a Sdl2TtfContext,
+    font_paths: HashMap<String, String>,
+    font_cache: HashMap<(String, u16), Font<'a, 'static>>,
+    text_cache: HashMap<TextKey, CachedText<'a>>,
     background_color: Color,
+    target: Option<String>,
+    screenshots: Vec<String>,
+    capture: Option<(String, u64)>,
+    frame: DrawFrame,
+    frame_instant: Instant,
+    interpolate: bool,
+    interpolation_done: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    font: String,
+    size: u16,
+    text: String,
+    color: Color,
+}
+
+struct CachedText<'a> {
+    texture: Texture<'a>,
+    used: bool,
+}
+
+const CHUNK_SIZE: u32 = 16;
+
+struct CachedTilemap {
+    layers: Vec<CachedTileLayer>,
+}
+
+// Tile layers are pre-rendered into textures of CHUNK_SIZE by CHUNK_SIZE
+// tiles, stored in the texture cache. Empty chunks have no texture.
+struct CachedTileLayer {
+    name: String,
+    visible: bool,
+    chunks: Vec<(String, Rect)>,
 }
 
 impl<'a> WindowModule<'a> {
     pub fn new(
         canvas: WindowCanvas,
         texture_creator: &'a TextureCreator<WindowContext>,
+        ttf: &'a Sdl2TtfContext,
     ) -> Result<Self, String> {
         Ok(WindowModule {
             canvas,
             texture_creator,
             texture_cache: HashMap::new(),
+            sprites: HashMap::new(),
+            bitmap_fonts: HashMap::new(),
+            tilemaps: HashMap::new(),
+            ttf,
+            font_paths: HashMap::new(),
+            font_cache: HashMap::new(),
+            text_cache: HashMap::new(),
             background_color: Color::BLACK,
+            target: None,
+            screenshots: vec![],
+            capture: None,
+            frame: DrawFrame::default(),
+            frame_instant: Instant::now(),
+            interpolate: true,
+            interpolation_done: false,
         })
     }
 
-    pub fn update(&mut self, state: &SharedState) -> Result<(), String> {
+    // Without interpolation frames are drawn as they were at the end of their
+    // tick, however long ago that was.
+    pub(crate) fn set_interpolate(&mut self, interpolate: bool) {
+        self.interpolate = interpolate;
+    }
+
+    // Only presents when something was drawn, as the window keeps showing
+    // the last presented frame otherwise. Returns whether it presented.
+    pub fn update(&mut self, state: &SharedState) -> Result<bool, String> {
+        let redraw = self.render(state)?;
+        if redraw {
+            self.canvas.present();
+        }
+        Ok(redraw)
+    }
+
+    // Handles the pending requests and draws the latest frame, if it changed,
+    // without presenting it. Returns whether anything was drawn.
+    pub(crate) fn render(&mut self, state: &SharedState) -> Result<bool, String> {
         for request in state.take_window_requests()? {
             self.handle_request(&request)?;
         }
 
         // let instant = Instant::now();
 
-        if let Some(draw_data) = state.lock_draw_data()? {
+        let frame = state.take_draw_data()?;
+        let new_frame = frame.is_some();
+        // Interpolated frames are redrawn until they reach the current tick.
+        let interpolating =
+            self.interpolate && self.frame.is_interpolated() && !self.interpolation_done;
+        let redraw = new_frame || interpolating || !self.screenshots.is_empty();
+        if let Some(mut frame) = frame {
+            frame
+                .commands
+                .sort_by_key(|command| (command.target.is_none(), command.layer));
+            self.frame = frame;
+            self.frame_instant = Instant::now();
+            self.interpolation_done = false;
+        }
+
+        if redraw {
             self.canvas.set_draw_color(self.background_color);
             self.canvas.clear();
 
-            for draw_data in draw_data.iter() {
-                self.draw(draw_data)?;
+            // Redraws skip the commands drawing into targets, so only the
+            // text of whole frames tells what is still in use.
+            if new_frame {
+                for text in self.text_cache.values_mut() {
+                    text.used = false;
+                }
+            }
+            let frame = mem::take(&mut self.frame);
+            let result = self.draw_frame(&frame, new_frame);
+            self.frame = frame;
+            let reset = self.set_target(None);
+            result?;
+            reset?;
+            if new_frame {
+                self.text_cache.retain(|_, text| text.used);
+            }
+
+            // The contents of the canvas are undefined after present, so
+            // frames are read back before it.
+            if let Some((dir, index)) = &mut self.capture {
+                let path = format!("{dir}/{index:06}.png");
+                *index += 1;
+                self.save_screenshot(&path)?;
+            }
+            for path in mem::take(&mut self.screenshots) {
+                self.save_screenshot(&path)?;
             }
         }
 
         // println!("Window update took {}us", instant.elapsed().as_micros());
 
-        self.canvas.present();
+        Ok(redraw)
+    }
+
+    // Targets keep their contents between redraws, so the commands drawing
+    // into them only run once for each frame sent by the world.
+    fn draw_frame(&mut self, frame: &DrawFrame, draw_targets: bool) -> Result<(), String> {
+        let t = if !self.interpolate || frame.tick_duration.is_zero() {
+            1.0
+        } else {
+            (self.frame_instant.elapsed().as_secs_f32() / frame.tick_duration.as_secs_f32())
+                .min(1.0)
+        };
+        self.interpolation_done = t >= 1.0;
+        let camera = frame.previous_camera.lerp(&frame.camera, t);
+
+        for command in frame.commands.iter() {
+            if command.target.is_some() && !draw_targets {
+                continue;
+            }
+            if command.target != self.target {
+                self.set_target(command.target.as_deref())?;
+            }
+            let camera = match command.space {
+                DrawSpace::World => camera,
+                DrawSpace::Screen => Camera::default(),
+            };
+            self.draw_with_camera(&command.interpolated(t), &camera, &command.style)?;
+        }
+        Ok(())
+    }
+
+    // Canvas::with_texture_canvas only lends the canvas to a closure, which
+    // can't draw through the module, so the target is switched directly.
+    fn set_target(&mut self, target: Option<&str>) -> Result<(), String> {
+        let texture = match target {
+            Some(id) => get_texture(&self.texture_cache, id)?.raw(),
+            None => ptr::null_mut(),
+        };
+        if unsafe { sdl2::sys::SDL_SetRenderTarget(self.canvas.raw(), texture) } != 0 {
+            return Err(sdl2::get_error());
+        }
+        self.target = target.map(str::to_string);
         Ok(())
     }
 
+    fn draw_with_camera(
+        &mut self,
+        draw_data: &DrawData,
+        camera: &Camera,
+        style: &DrawStyle,
+    ) -> Result<(), String> {
+        if let DrawData::Tilemap { .. } = draw_data {
+            for chunk in self.tilemap_chunks(draw_data, camera)? {
+                self.draw_with_camera(&chunk, camera, style)?;
+            }
+            return Ok(());
+        }
+
+        if let DrawData::Text { .. } | DrawData::BitmapText { .. } = draw_data {
+            return self.draw_text(draw_data, camera, style);
+        }
+
+        if let Some(mode) = style.blend_mode {
+            if mode != BlendMode::Blend && uses_gfx(draw_data) {
+                return Err(format!(
+                    "blend mode {mode:?} only works on rectangles, textures and text"
+                ));
+            }
+        }
+
+        let blend_mode = self.canvas.blend_mode();
+        if let Some(mode) = style.blend_mode {
+            self.canvas.set_blend_mode(mode);
+        }
+        let result = if camera.is_identity() {
+            self.draw(draw_data, style)
+        } else {
+            self.draw(&camera.apply(draw_data), style)
+        };
+        self.canvas.set_blend_mode(blend_mode);
+        result
+    }
+
+    // Returns the chunks of a tilemap that are visible through the camera as
+    // texture draws in world space.
+    fn tilemap_chunks(
+        &self,
+        draw_data: &DrawData,
+        camera: &Camera,
+    ) -> Result<Vec<DrawData>, String> {
+        let (id, layer, x, y) = match draw_data {
+            DrawData::Tilemap { id, layer, x, y } => (id, layer, *x, *y),
+            _ => return Ok(vec![]),
+        };
+        let tilemap = match self.tilemaps.get(id) {
+            Some(tilemap) => tilemap,
+            None => return Err(format!("tilemap not found: {id}")),
+        };
+
+        let (width, height) = match (&self.target, self.canvas.logical_size()) {
+            (Some(target), _) => {
+                let query = get_texture(&self.texture_cache, target)?.query();
+                (query.width, query.height)
+            }
+            (None, (0, 0)) => self.canvas.output_size()?,
+            (None, size) => size,
+        };
+        let (view_x, view_y, view_w, view_h) = camera.visible_area(width, height);
+        let view = Rect::new(
+            view_x.floor() as i32 - x,
+            view_y.floor() as i32 - y,
+            view_w.ceil() as u32 + 1,
+            view_h.ceil() as u32 + 1,
+        );
+
+        Ok(tilemap
+            .layers
+            .iter()
+            .filter(|l| match layer {
+                Some(layer) => l.name == *layer,
+                None => l.visible,
+            })
+            .flat_map(|layer| layer.chunks.iter())
+            .filter(|(_, rect)| rect.has_intersection(view))
+            .map(|(id, rect)| DrawData::Texture {
+                id: id.clone(),
+                src: None,
+                dst: Some(Rect::new(
+                    rect.x() + x,
+                    rect.y() + y,
+                    rect.width(),
+                    rect.height(),
+                )),
+            })
+            .collect())
+    }
+
     fn handle_request(&mut self, request: &WindowRequest) -> Result<(), String> {
         match request {
+            WindowRequest::CreateTarget { id, width, height } => {
+                self.create_target(id, *width, *height)?
+            }
             WindowRequest::EnableFullscreen => self
                 .canvas
                 .window_mut()
@@ -71,32 +325,265 @@ impl<'a> WindowModule<'a> {
                 .canvas
                 .window_mut()
                 .set_fullscreen(FullscreenType::Off)?,
+            WindowRequest::Screenshot { path } => self.screenshots.push(path.clone()),
+            WindowRequest::StartCapture { dir } => {
+                fs::create_dir_all(dir).map_err(|e| format!("{dir}: {e}"))?;
+                self.capture = Some((dir.clone(), 0));
+            }
+            WindowRequest::StopCapture => self.capture = None,
             WindowRequest::SetBackgroundColor(color) => self.background_color = *color,
-            // WindowRequest::LoadTexture { id, path } => self.load_texture(id, path)?,
-            // WindowRequest::LoadTextureBytes { id, bytes } => self.load_texture_bytes(id, bytes)?,
+            WindowRequest::SetLogicalSize(w, h) => self.set_logical_size(*w, *h)?,
+            WindowRequest::SetScaleMode(mode) => self.set_scale_mode(*mode)?,
+            WindowRequest::LoadFont { id, path } => self.load_font(id, path)?,
+            WindowRequest::UnloadFont { id } => self.unload_font(id)?,
+            WindowRequest::LoadTexture { id, path } => self.load_texture(id, path)?,
+            WindowRequest::LoadTextureBytes { id, bytes } => self.load_texture_bytes(id, bytes)?,
+            WindowRequest::UnloadTexture { id } => self.unload_texture(id)?,
+            WindowRequest::LoadAtlas(atlas) => self.load_atlas(atlas)?,
+            WindowRequest::UnloadAtlas { texture } => self.unload_atlas(texture),
+            WindowRequest::LoadBitmapFont { id, path } => self.load_bitmap_font(id, path)?,
+            WindowRequest::LoadGridFont {
+                id,
+                texture,
+                char_width,
+                char_height,
+                columns,
+                chars,
+            } => {
+                if *columns == 0 {
+                    return Err(format!("grid font needs at least one column: {id}"));
+                }
+                let font = BitmapFont::grid(texture, *char_width, *char_height, *columns, chars);
+                self.add_bitmap_font(id, font)?
+            }
+            WindowRequest::UnloadBitmapFont { id } => self.unload_bitmap_font(id)?,
+            WindowRequest::LoadTilemap { id, map } => self.load_tilemap(id, map)?,
+            WindowRequest::UnloadTilemap { id } => self.unload_tilemap(id)?,
         };
         Ok(())
     }
 
-    fn draw(&mut self, draw_data: &DrawData) -> Result<(), String> {
+    pub fn set_logical_size(&mut self, width: u32, height: u32) -> Result<(), String> {
+        self.canvas
+            .set_logical_size(width, height)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn set_scale_mode(&mut self, mode: ScaleMode) -> Result<(), String> {
+        self.canvas.set_integer_scale(mode == ScaleMode::Integer)
+    }
+
+    fn save_screenshot(&self, path: &str) -> Result<(), String> {
+        self.read_pixels()?.save(path)
+    }
+
+    pub(crate) fn read_pixels(&self) -> Result<Image, String> {
+        // With a logical size only the letterboxed viewport is read back. The
+        // viewport is in logical units, but pixels are read in output ones.
+        let viewport = self.canvas.viewport();
+        let (scale_x, scale_y) = self.canvas.scale();
+        let rect = Rect::new(
+            (viewport.x() as f32 * scale_x).round() as i32,
+            (viewport.y() as f32 * scale_y).round() as i32,
+            (viewport.width() as f32 * scale_x).round() as u32,
+            (viewport.height() as f32 * scale_y).round() as u32,
+        );
+
+        let pixels = self.canvas.read_pixels(rect, PixelFormatEnum::ABGR8888)?;
+        Image::new(rect.width(), rect.height(), pixels)
+    }
+
+    fn draw(&mut self, draw_data: &DrawData, style: &DrawStyle) -> Result<(), String> {
         match draw_data {
             DrawData::Rectangle { rect, color } => {
-                self.canvas.set_draw_color(*color);
+                self.canvas.set_draw_color(styled_color(*color, style));
                 self.canvas.draw_rect(*rect)?;
             }
             DrawData::FilledRectangle { rect, color } => {
-                self.canvas.set_draw_color(*color);
+                self.canvas.set_draw_color(styled_color(*color, style));
                 self.canvas.fill_rect(*rect)?;
             }
+            DrawData::RoundedRectangle { rect, rad, color } => {
+                let (x1, y1, x2, y2) = rect_corners(rect);
+                self.canvas
+                    .rounded_rectangle(x1, y1, x2, y2, *rad, styled_color(*color, style))?;
+            }
+            DrawData::FilledRoundedRectangle { rect, rad, color } => {
+                let (x1, y1, x2, y2) = rect_corners(rect);
+                self.canvas
+                    .rounded_box(x1, y1, x2, y2, *rad, styled_color(*color, style))?;
+            }
+            DrawData::Point { x, y, color } => {
+                self.canvas.pixel(*x, *y, styled_color(*color, style))?;
+            }
+            DrawData::Line {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            } => {
+                self.canvas
+                    .line(*x1, *y1, *x2, *y2, styled_color(*color, style))?;
+            }
+            DrawData::AaLine {
+                x1,
+                y1,
+                x2,
+                y2,
+                color,
+            } => {
+                self.canvas
+                    .aa_line(*x1, *y1, *x2, *y2, styled_color(*color, style))?;
+            }
+            DrawData::ThickLine {
+                x1,
+                y1,
+                x2,
+                y2,
+                width,
+                color,
+            } => {
+                self.canvas
+                    .thick_line(*x1, *y1, *x2, *y2, *width, styled_color(*color, style))?;
+            }
             DrawData::Circle { x, y, rad, color } => {
-                self.canvas.circle(*x, *y, *rad, *color)?;
+                self.canvas
+                    .circle(*x, *y, *rad, styled_color(*color, style))?;
+            }
+            DrawData::AaCircle { x, y, rad, color } => {
+                self.canvas
+                    .aa_circle(*x, *y, *rad, styled_color(*color, style))?;
             }
             DrawData::FilledCircle { x, y, rad, color } => {
-                self.canvas.filled_circle(*x, *y, *rad, *color)?;
+                self.canvas
+                    .filled_circle(*x, *y, *rad, styled_color(*color, style))?;
+            }
+            DrawData::Ellipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } => {
+                self.canvas
+                    .ellipse(*x, *y, *rx, *ry, styled_color(*color, style))?;
+            }
+            DrawData::AaEllipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } => {
+                self.canvas
+                    .aa_ellipse(*x, *y, *rx, *ry, styled_color(*color, style))?;
+            }
+            DrawData::FilledEllipse {
+                x,
+                y,
+                rx,
+                ry,
+                color,
+            } => {
+                self.canvas
+                    .filled_ellipse(*x, *y, *rx, *ry, styled_color(*color, style))?;
+            }
+            DrawData::Arc {
+                x,
+                y,
+                rad,
+                start,
+                end,
+                color,
+            } => {
+                self.canvas
+                    .arc(*x, *y, *rad, *start, *end, styled_color(*color, style))?;
+            }
+            DrawData::Pie {
+                x,
+                y,
+                rad,
+                start,
+                end,
+                color,
+            } => {
+                self.canvas
+                    .pie(*x, *y, *rad, *start, *end, styled_color(*color, style))?;
+            }
+            DrawData::FilledPie {
+                x,
+                y,
+                rad,
+                start,
+                end,
+                color,
+            } => {
+                self.canvas
+                    .filled_pie(*x, *y, *rad, *start, *end, styled_color(*color, style))?;
+            }
+            DrawData::Triangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                color,
+            } => {
+                self.canvas
+                    .trigon(*x1, *y1, *x2, *y2, *x3, *y3, styled_color(*color, style))?;
+            }
+            DrawData::AaTriangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                color,
+            } => {
+                self.canvas
+                    .aa_trigon(*x1, *y1, *x2, *y2, *x3, *y3, styled_color(*color, style))?;
+            }
+            DrawData::FilledTriangle {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                color,
+            } => {
+                self.canvas.filled_trigon(
+                    *x1,
+                    *y1,
+                    *x2,
+                    *y2,
+                    *x3,
+                    *y3,
+                    styled_color(*color, style),
+                )?;
+            }
+            DrawData::Polygon { points, color } => {
+                let (vx, vy): (Vec<_>, Vec<_>) = points.iter().copied().unzip();
+                self.canvas.polygon(&vx, &vy, styled_color(*color, style))?;
+            }
+            DrawData::AaPolygon { points, color } => {
+                let (vx, vy): (Vec<_>, Vec<_>) = points.iter().copied().unzip();
+                self.canvas
+                    .aa_polygon(&vx, &vy, styled_color(*color, style))?;
+            }
+            DrawData::FilledPolygon { points, color } => {
+                let (vx, vy): (Vec<_>, Vec<_>) = points.iter().copied().unzip();
+                self.canvas
+                    .filled_polygon(&vx, &vy, styled_color(*color, style))?;
             }
             DrawData::Texture { id, src, dst } => {
-                let texture = self.get_texture(&id)?;
-                self.canvas.copy(&texture, *src, *dst)?;
+                let texture = get_texture_mut(&mut self.texture_cache, id)?;
+                copy_styled(&mut self.canvas, texture, style, |canvas, texture| {
+                    canvas.copy(texture, *src, *dst)
+                })?;
             }
             DrawData::TextureEx {
                 id,
@@ -107,31 +594,226 @@ impl<'a> WindowModule<'a> {
                 flip_h,
                 flip_v,
             } => {
-                let texture = self.get_texture(&id)?;
-                self.canvas
-                    .copy_ex(&texture, *src, *dst, *angle, *center, *flip_h, *flip_v)?;
+                let texture = get_texture_mut(&mut self.texture_cache, id)?;
+                copy_styled(&mut self.canvas, texture, style, |canvas, texture| {
+                    canvas.copy_ex(texture, *src, *dst, *angle, *center, *flip_h, *flip_v)
+                })?;
+            }
+            DrawData::Sprite { name, dst } => {
+                let (id, src) = get_sprite(&self.sprites, name)?;
+                let texture = get_texture_mut(&mut self.texture_cache, id)?;
+                copy_styled(&mut self.canvas, texture, style, |canvas, texture| {
+                    canvas.copy(texture, *src, *dst)
+                })?;
+            }
+            DrawData::SpriteEx {
+                name,
+                dst,
+                center,
+                angle,
+                flip_h,
+                flip_v,
+            } => {
+                let (id, src) = get_sprite(&self.sprites, name)?;
+                let texture = get_texture_mut(&mut self.texture_cache, id)?;
+                copy_styled(&mut self.canvas, texture, style, |canvas, texture| {
+                    canvas.copy_ex(texture, *src, *dst, *angle, *center, *flip_h, *flip_v)
+                })?;
+            }
+            DrawData::Tilemap { .. } => {
+                self.draw_with_camera(draw_data, &Camera::default(), style)?
+            }
+            DrawData::Clear { color } => {
+                self.canvas.set_draw_color(*color);
+                self.canvas.clear();
+            }
+            DrawData::Text { .. } | DrawData::BitmapText { .. } => {
+                self.draw_text(draw_data, &Camera::default(), style)?
+            }
+        }
+        Ok(())
+    }
+
+    // Text is laid out in world space and every texture copy is placed
+    // through the camera, so it scales and rotates like sprites do.
+    fn draw_text(
+        &mut self,
+        draw_data: &DrawData,
+        camera: &Camera,
+        style: &DrawStyle,
+    ) -> Result<(), String> {
+        match draw_data {
+            DrawData::Text {
+                font,
+                text,
+                x,
+                y,
+                color,
+                size,
+            } => {
+                if text.is_empty() {
+                    return Ok(());
+                }
+                let key = TextKey {
+                    font: font.clone(),
+                    size: *size,
+                    text: text.clone(),
+                    color: *color,
+                };
+                if !self.text_cache.contains_key(&key) {
+                    let texture = self.render_text(font, *size, text, *color)?;
+                    self.text_cache.insert(
+                        key.clone(),
+                        CachedText {
+                            texture,
+                            used: false,
+                        },
+                    );
+                }
+                let cached = self.text_cache.get_mut(&key).unwrap();
+                cached.used = true;
+                let query = cached.texture.query();
+                let dst = Rect::new(*x, *y, query.width, query.height);
+                copy_placed(
+                    &mut self.canvas,
+                    &mut cached.texture,
+                    style,
+                    None,
+                    dst,
+                    camera,
+                )?;
+            }
+            DrawData::BitmapText {
+                font,
+                text,
+                x,
+                y,
+                align,
+                max_width,
+                color,
+                colors,
+            } => {
+                let font = match self.bitmap_fonts.get(font) {
+                    Some(font) => font,
+                    None => return Err(format!("bitmap font not found: {font}")),
+                };
+                for placed in font.layout(text, *max_width, *align) {
+                    let glyph = placed.glyph;
+                    let page = font
+                        .pages
+                        .get(glyph.page)
+                        .ok_or(format!("bitmap font page not found: {}", glyph.page))?;
+                    let texture = get_texture_mut(&mut self.texture_cache, page)?;
+                    let color = colors.get(placed.index).unwrap_or(color);
+                    let glyph_style = DrawStyle {
+                        alpha: modulate(color.a, style.alpha),
+                        color_mod: Color::RGB(
+                            modulate(color.r, style.color_mod.r),
+                            modulate(color.g, style.color_mod.g),
+                            modulate(color.b, style.color_mod.b),
+                        ),
+                        blend_mode: style.blend_mode,
+                    };
+                    let src = Rect::new(glyph.x, glyph.y, glyph.width, glyph.height);
+                    let dst = Rect::new(x + placed.x, y + placed.y, glyph.width, glyph.height);
+                    copy_placed(
+                        &mut self.canvas,
+                        texture,
+                        &glyph_style,
+                        Some(src),
+                        dst,
+                        camera,
+                    )?;
+                }
             }
+            _ => (),
         }
         Ok(())
     }
 
-    fn load_texture(&mut self, id: &'a str, path: &str) -> Result<(), String> {
+    fn load_font(&mut self, id: &str, path: &str) -> Result<(), String> {
+        if self.font_paths.contains_key(id) {
+            return Err(format!("font already exists: {id}"));
+        }
+
+        self.font_paths.insert(id.to_string(), path.to_string());
+        Ok(())
+    }
+
+    fn unload_font(&mut self, id: &str) -> Result<(), String> {
+        match self.font_paths.remove(id) {
+            Some(_) => {
+                self.font_cache.retain(|(font, _), _| font != id);
+                self.text_cache.retain(|key, _| key.font != id);
+                Ok(())
+            }
+            None => Err(format!("font not found: {id}")),
+        }
+    }
+
+    fn render_text(
+        &mut self,
+        font: &str,
+        size: u16,
+        text: &str,
+        color: Color,
+    ) -> Result<Texture<'a>, String> {
+        let key = (font.to_string(), size);
+        if !self.font_cache.contains_key(&key) {
+            let path = match self.font_paths.get(font) {
+                Some(path) => path,
+                None => return Err(format!("font not found: {font}")),
+            };
+            let loaded = self.ttf.load_font(path, size)?;
+            self.font_cache.insert(key.clone(), loaded);
+        }
+
+        let surface = self.font_cache[&key]
+            .render(text)
+            .blended(color)
+            .map_err(|e| e.to_string())?;
+        self.texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())
+    }
+
+    fn load_texture(&mut self, id: &str, path: &str) -> Result<(), String> {
         if self.texture_cache.contains_key(id) {
             return Err(format!("texture already exists: {id}"));
         }
 
         let texture = self.texture_creator.load_texture(path)?;
-        self.texture_cache.insert(id, Rc::new(texture));
+        self.texture_cache.insert(id.to_string(), texture);
         Ok(())
     }
 
-    fn load_texture_bytes(&mut self, id: &'a str, bytes: &[u8]) -> Result<(), String> {
+    fn load_texture_bytes(&mut self, id: &str, bytes: &[u8]) -> Result<(), String> {
         if self.texture_cache.contains_key(id) {
             return Err(format!("texture already exists: {id}"));
         }
 
         let texture = self.texture_creator.load_texture_bytes(bytes)?;
-        self.texture_cache.insert(id, Rc::new(texture));
+        self.texture_cache.insert(id.to_string(), texture);
+        Ok(())
+    }
+
+    fn create_target(&mut self, id: &str, width: u32, height: u32) -> Result<(), String> {
+        if self.texture_cache.contains_key(id) {
+            return Err(format!("texture already exists: {id}"));
+        }
+
+        let mut texture = self
+            .texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA8888, width, height)
+            .map_err(|e| e.to_string())?;
+        texture.set_blend_mode(BlendMode::Blend);
+        self.canvas
+            .with_texture_canvas(&mut texture, |canvas| {
+                canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+                canvas.clear();
+            })
+            .map_err(|e| e.to_string())?;
+        self.texture_cache.insert(id.to_string(), texture);
         Ok(())
     }
 
@@ -142,10 +824,650 @@ impl<'a> WindowModule<'a> {
         }
     }
 
-    fn get_texture(&self, id: &str) -> Result<Rc<Texture<'a>>, String> {
-        match self.texture_cache.get(id) {
-            Some(texture) => Ok(Rc::clone(texture)),
-            None => Err(format!("texture not found: {id}")),
+    fn load_atlas(&mut self, atlas: &Atlas) -> Result<(), String> {
+        if let Some((name, _)) = atlas
+            .regions()
+            .find(|(name, _)| self.sprites.contains_key(*name))
+        {
+            return Err(format!("sprite already exists: {name}"));
+        }
+
+        for (name, region) in atlas.regions() {
+            let sprite = (atlas.texture.clone(), region.rect);
+            self.sprites.insert(name.to_string(), sprite);
+        }
+        Ok(())
+    }
+
+    fn unload_atlas(&mut self, texture: &str) {
+        self.sprites.retain(|_, (id, _)| id != texture);
+    }
+
+    fn load_tilemap(&mut self, id: &str, map: &Tilemap) -> Result<(), String> {
+        if self.tilemaps.contains_key(id) {
+            return Err(format!("tilemap already exists: {id}"));
         }
+
+        for tileset in map.tilesets.iter() {
+            if !self.texture_cache.contains_key(&tileset.image) {
+                self.load_texture(&tileset.image, &tileset.image)?;
+            }
+        }
+
+        let mut layers = vec![];
+        for (index, layer) in map.layers.iter().enumerate() {
+            let mut chunks = vec![];
+            for chunk_y in 0..layer.height.div_ceil(CHUNK_SIZE) {
+                for chunk_x in 0..layer.width.div_ceil(CHUNK_SIZE) {
+                    let chunk_id = format!("{id}/{index}/{chunk_x},{chunk_y}");
+                    if let Some(rect) =
+                        self.render_chunk(&chunk_id, map, layer, chunk_x, chunk_y)?
+                    {
+                        chunks.push((chunk_id, rect));
+                    }
+                }
+            }
+            layers.push(CachedTileLayer {
+                name: layer.name.clone(),
+                visible: layer.visible,
+                chunks,
+            });
+        }
+
+        self.tilemaps
+            .insert(id.to_string(), CachedTilemap { layers });
+        Ok(())
+    }
+
+    fn render_chunk(
+        &mut self,
+        chunk_id: &str,
+        map: &Tilemap,
+        layer: &TileLayer,
+        chunk_x: u32,
+        chunk_y: u32,
+    ) -> Result<Option<Rect>, String> {
+        let (first_x, first_y) = (chunk_x * CHUNK_SIZE, chunk_y * CHUNK_SIZE);
+        let columns = CHUNK_SIZE.min(layer.width - first_x);
+        let rows = CHUNK_SIZE.min(layer.height - first_y);
+        let tiles: Vec<_> = (0..rows)
+            .flat_map(|y| (0..columns).map(move |x| (x, y)))
+            .filter_map(|(x, y)| {
+                let gid = layer.tiles[((first_y + y) * layer.width + first_x + x) as usize];
+                Some((x, y, gid, map.tileset(gid)?))
+            })
+            .collect();
+        if tiles.is_empty() {
+            return Ok(None);
+        }
+
+        // Tiles taller or wider than the map's tiles stick out at the top and
+        // the right, so the chunk is padded to fit them.
+        let (pad_x, pad_y) = tiles.iter().fold((0, 0), |(pad_x, pad_y), (.., tileset)| {
+            (
+                pad_x.max(tileset.tile_width.saturating_sub(map.tile_width)),
+                pad_y.max(tileset.tile_height.saturating_sub(map.tile_height)),
+            )
+        });
+        let width = columns * map.tile_width + pad_x;
+        let height = rows * map.tile_height + pad_y;
+        let mut chunk = self
+            .texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA8888, width, height)
+            .map_err(|e| e.to_string())?;
+        chunk.set_blend_mode(BlendMode::Blend);
+        chunk.set_alpha_mod((layer.opacity.clamp(0.0, 1.0) * 255.0) as u8);
+
+        let mut result = Ok(());
+        let texture_cache = &self.texture_cache;
+        self.canvas
+            .with_texture_canvas(&mut chunk, |canvas| {
+                canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+                canvas.clear();
+                for (x, y, gid, tileset) in tiles.iter() {
+                    let texture = match get_texture(texture_cache, &tileset.image) {
+                        Ok(texture) => texture,
+                        Err(e) => {
+                            result = Err(e);
+                            return;
+                        }
+                    };
+                    let dst = Rect::new(
+                        (x * map.tile_width) as i32,
+                        ((y + 1) * map.tile_height + pad_y) as i32 - tileset.tile_height as i32,
+                        tileset.tile_width,
+                        tileset.tile_height,
+                    );
+                    let (angle, flip_h, flip_v) = tile_orientation(*gid);
+                    let src = tileset.tile_rect(*gid);
+                    if let Err(e) = canvas.copy_ex(texture, src, dst, angle, None, flip_h, flip_v) {
+                        result = Err(e);
+                        return;
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        result?;
+
+        self.texture_cache.insert(chunk_id.to_string(), chunk);
+        Ok(Some(Rect::new(
+            (first_x * map.tile_width) as i32,
+            (first_y * map.tile_height) as i32 - pad_y as i32,
+            width,
+            height,
+        )))
+    }
+
+    fn unload_tilemap(&mut self, id: &str) -> Result<(), String> {
+        match self.tilemaps.remove(id) {
+            Some(tilemap) => {
+                for (chunk_id, _) in tilemap.layers.iter().flat_map(|l| l.chunks.iter()) {
+                    self.texture_cache.remove(chunk_id);
+                }
+                Ok(())
+            }
+            None => Err(format!("tilemap not found: {id}")),
+        }
+    }
+
+    fn load_bitmap_font(&mut self, id: &str, path: &str) -> Result<(), String> {
+        let font = BitmapFont::from_fnt_file(path)?;
+        for page in font.pages.iter() {
+            if !self.texture_cache.contains_key(page) {
+                self.load_texture(page, page)?;
+            }
+        }
+        self.add_bitmap_font(id, font)
+    }
+
+    fn add_bitmap_font(&mut self, id: &str, font: BitmapFont) -> Result<(), String> {
+        if self.bitmap_fonts.contains_key(id) {
+            return Err(format!("bitmap font already exists: {id}"));
+        }
+
+        self.bitmap_fonts.insert(id.to_string(), font);
+        Ok(())
+    }
+
+    fn unload_bitmap_font(&mut self, id: &str) -> Result<(), String> {
+        match self.bitmap_fonts.remove(id) {
+            Some(_) => Ok(()),
+            None => Err(format!("bitmap font not found: {id}")),
+        }
+    }
+}
+
+fn get_texture<'t, 'a>(
+    cache: &'t HashMap<String, Texture<'a>>,
+    id: &str,
+) -> Result<&'t Texture<'a>, String> {
+    match cache.get(id) {
+        Some(texture) => Ok(texture),
+        None => Err(format!("texture not found: {id}")),
+    }
+}
+
+fn get_texture_mut<'t, 'a>(
+    cache: &'t mut HashMap<String, Texture<'a>>,
+    id: &str,
+) -> Result<&'t mut Texture<'a>, String> {
+    match cache.get_mut(id) {
+        Some(texture) => Ok(texture),
+        None => Err(format!("texture not found: {id}")),
+    }
+}
+
+// Copies to a rect in world space, which the camera moves, scales and
+// rotates.
+fn copy_placed(
+    canvas: &mut WindowCanvas,
+    texture: &mut Texture,
+    style: &DrawStyle,
+    src: Option<Rect>,
+    dst: Rect,
+    camera: &Camera,
+) -> Result<(), String> {
+    if camera.is_identity() {
+        return copy_styled(canvas, texture, style, |canvas, texture| {
+            canvas.copy(texture, src, dst)
+        });
+    }
+    let (dst, center, angle) = camera.texture(dst, None, 0.0);
+    copy_styled(canvas, texture, style, |canvas, texture| {
+        canvas.copy_ex(texture, src, dst, angle, center, false, false)
+    })
+}
+
+// Multiplies the texture's own modulation by the style for a single copy.
+fn copy_styled(
+    canvas: &mut WindowCanvas,
+    texture: &mut Texture,
+    style: &DrawStyle,
+    copy: impl FnOnce(&mut WindowCanvas, &Texture) -> Result<(), String>,
+) -> Result<(), String> {
+    if *style == DrawStyle::default() {
+        return copy(canvas, texture);
+    }
+
+    let (r, g, b) = texture.color_mod();
+    let alpha = texture.alpha_mod();
+    let blend_mode = texture.blend_mode();
+    let color = style.color_mod;
+    texture.set_color_mod(
+        modulate(r, color.r),
+        modulate(g, color.g),
+        modulate(b, color.b),
+    );
+    texture.set_alpha_mod(modulate(alpha, style.alpha));
+    if let Some(mode) = style.blend_mode {
+        texture.set_blend_mode(mode);
+    }
+    let result = copy(canvas, texture);
+    texture.set_color_mod(r, g, b);
+    texture.set_alpha_mod(alpha);
+    texture.set_blend_mode(blend_mode);
+    result
+}
+
+// Applies the alpha and color mod of the style to a primitive's color.
+fn styled_color(color: Color, style: &DrawStyle) -> Color {
+    let tint = style.color_mod;
+    Color::RGBA(
+        modulate(color.r, tint.r),
+        modulate(color.g, tint.g),
+        modulate(color.b, tint.b),
+        modulate(color.a, style.alpha),
+    )
+}
+
+// SDL2_gfx sets its own blend mode for every primitive it draws, blending
+// translucent colors and replacing with opaque ones.
+fn uses_gfx(draw_data: &DrawData) -> bool {
+    !matches!(
+        draw_data,
+        DrawData::Rectangle { .. }
+            | DrawData::FilledRectangle { .. }
+            | DrawData::Texture { .. }
+            | DrawData::TextureEx { .. }
+            | DrawData::Sprite { .. }
+            | DrawData::SpriteEx { .. }
+            | DrawData::Text { .. }
+            | DrawData::BitmapText { .. }
+            | DrawData::Tilemap { .. }
+            | DrawData::Clear { .. }
+    )
+}
+
+fn modulate(a: u8, b: u8) -> u8 {
+    (a as u16 * b as u16 / 255) as u8
+}
+
+fn rect_corners(rect: &Rect) -> (i16, i16, i16, i16) {
+    (
+        rect.left() as i16,
+        rect.top() as i16,
+        (rect.right() - 1) as i16,
+        (rect.bottom() - 1) as i16,
+    )
+}
+
+fn get_sprite<'t>(
+    sprites: &'t HashMap<String, (String, Rect)>,
+    name: &str,
+) -> Result<&'t (String, Rect), String> {
+    match sprites.get(name) {
+        Some(sprite) => Ok(sprite),
+        None => Err(format!("sprite not found: {name}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, thread, time::Duration};
+
+    use super::*;
+    use crate::{
+        golden::with_offscreen_window, render_offscreen, DrawCommand, TextAlign, Transform,
+    };
+
+    // Blends translucent blue into the target every time it's drawn, and
+    // moves a rectangle between the ticks.
+    fn interpolated_frame(state: &SharedState) -> Result<(), String> {
+        let commands = vec![
+            DrawData::FilledRectangle {
+                rect: Rect::new(0, 0, 8, 8),
+                color: Color::RGBA(0, 0, 255, 64),
+            }
+            .on_target("target")
+            .blend_mode(BlendMode::Blend),
+            DrawData::Texture {
+                id: "target".to_string(),
+                src: None,
+                dst: None,
+            }
+            .into(),
+            DrawCommand::new(DrawData::FilledRectangle {
+                rect: Rect::new(0, 0, 2, 2),
+                color: Color::RED,
+            })
+            .interpolate(Transform::new(0.0, 0.0, 0.0), Transform::new(4.0, 0.0, 0.0)),
+        ];
+        state.set_draw_data(DrawFrame {
+            commands,
+            tick_duration: Duration::from_millis(100),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn interpolated_frames_are_redrawn_until_they_reach_the_tick() {
+        with_offscreen_window(8, 8, |window| {
+            let state = SharedState::new();
+            state.send_window_request(WindowRequest::CreateTarget {
+                id: "target".to_string(),
+                width: 8,
+                height: 8,
+            })?;
+            interpolated_frame(&state)?;
+            assert!(window.render(&state)?);
+            let blue = window.read_pixels()?.pixel(6, 6);
+            assert!(window.render(&state)?);
+
+            thread::sleep(Duration::from_millis(110));
+            assert!(window.render(&state)?);
+            let image = window.read_pixels()?;
+            assert!(!window.render(&state)?);
+
+            // The target was only drawn into once, and the rectangle reached
+            // its position of the current tick.
+            assert_eq!(image.pixel(6, 6), blue);
+            assert_eq!(image.pixel(0, 0), Some(Color::RED));
+            assert_eq!(image.pixel(4, 0), blue);
+
+            interpolated_frame(&state)?;
+            assert!(window.render(&state)?);
+            assert_ne!(window.read_pixels()?.pixel(6, 6), blue);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn text_drawn_into_targets_stays_cached_between_frames() {
+        with_offscreen_window(8, 8, |window| {
+            let key = TextKey {
+                font: "font".to_string(),
+                size: 8,
+                text: "hi".to_string(),
+                color: Color::WHITE,
+            };
+            let texture = window
+                .texture_creator
+                .create_texture_target(PixelFormatEnum::RGBA8888, 4, 4)
+                .map_err(|e| e.to_string())?;
+            let cached = CachedText {
+                texture,
+                used: false,
+            };
+            window.text_cache.insert(key.clone(), cached);
+
+            let state = SharedState::new();
+            state.send_window_request(WindowRequest::CreateTarget {
+                id: "target".to_string(),
+                width: 8,
+                height: 8,
+            })?;
+            let text = DrawData::Text {
+                font: key.font.clone(),
+                text: key.text.clone(),
+                x: 0,
+                y: 0,
+                color: key.color,
+                size: key.size,
+            };
+            let moving = DrawCommand::new(DrawData::FilledRectangle {
+                rect: Rect::new(0, 0, 1, 1),
+                color: Color::RED,
+            })
+            .interpolate(Transform::new(0.0, 0.0, 0.0), Transform::new(4.0, 0.0, 0.0));
+            state.set_draw_data(DrawFrame {
+                commands: vec![text.on_target("target"), moving],
+                tick_duration: Duration::from_secs(10),
+                ..Default::default()
+            })?;
+            assert!(window.render(&state)?);
+            assert!(window.render(&state)?);
+            assert!(window.text_cache.contains_key(&key));
+
+            // Evicted once a whole frame goes by without it.
+            state.set_draw_data(DrawFrame::default())?;
+            window.render(&state)?;
+            assert!(!window.text_cache.contains_key(&key));
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn world_space_text_follows_the_camera_zoom() {
+        with_offscreen_window(8, 8, |window| {
+            let state = SharedState::new();
+            state.send_window_request(WindowRequest::CreateTarget {
+                id: "page".to_string(),
+                width: 4,
+                height: 4,
+            })?;
+            state.send_window_request(WindowRequest::LoadGridFont {
+                id: "font".to_string(),
+                texture: "page".to_string(),
+                char_width: 4,
+                char_height: 4,
+                columns: 1,
+                chars: "A".to_string(),
+            })?;
+            let commands = vec![
+                DrawData::FilledRectangle {
+                    rect: Rect::new(0, 0, 4, 4),
+                    color: Color::WHITE,
+                }
+                .on_target("page"),
+                DrawData::BitmapText {
+                    font: "font".to_string(),
+                    text: "A".to_string(),
+                    x: 0,
+                    y: 0,
+                    align: TextAlign::Left,
+                    max_width: None,
+                    color: Color::WHITE,
+                    colors: vec![],
+                }
+                .into(),
+            ];
+            let camera = Camera {
+                zoom: 2.0,
+                ..Default::default()
+            };
+            state.set_draw_data(DrawFrame {
+                commands,
+                camera,
+                previous_camera: camera,
+                ..Default::default()
+            })?;
+            window.render(&state)?;
+
+            let image = window.read_pixels()?;
+            assert_eq!(image.pixel(0, 0), Some(Color::WHITE));
+            assert_eq!(image.pixel(7, 7), Some(Color::WHITE));
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn style_alpha_and_color_mod_apply_to_primitives() {
+        let draw_data = [DrawCommand::new(DrawData::FilledRectangle {
+            rect: Rect::new(0, 0, 4, 4),
+            color: Color::WHITE,
+        })
+        .alpha(128)
+        .color_mod(Color::RED)
+        .blend_mode(BlendMode::Blend)];
+
+        let image = render_offscreen(4, 4, vec![], draw_data).unwrap();
+        let pixel = image.pixel(0, 0).unwrap();
+        assert!(pixel.r.abs_diff(128) <= 1, "{pixel:?}");
+        assert_eq!((pixel.g, pixel.b), (0, 0));
+    }
+
+    #[test]
+    fn gfx_primitives_reject_other_blend_modes() {
+        let circle = DrawCommand::new(DrawData::FilledCircle {
+            x: 2,
+            y: 2,
+            rad: 2,
+            color: Color::WHITE,
+        });
+        let draw_data = [circle.blend_mode(BlendMode::Add)];
+        assert!(render_offscreen(4, 4, vec![], draw_data).is_err());
+        let rect = DrawCommand::new(DrawData::FilledRectangle {
+            rect: Rect::new(0, 0, 4, 4),
+            color: Color::WHITE,
+        });
+        let draw_data = [rect.blend_mode(BlendMode::Add)];
+        assert!(render_offscreen(4, 4, vec![], draw_data).is_ok());
+    }
+
+    #[test]
+    fn draws_within_a_layer_keep_their_order() {
+        let requests = vec![WindowRequest::CreateTarget {
+            id: "sheet".to_string(),
+            width: 4,
+            height: 4,
+        }];
+        let sheet = |x| DrawData::Texture {
+            id: "sheet".to_string(),
+            src: None,
+            dst: Some(Rect::new(x, 0, 4, 4)),
+        };
+        let draw_data = vec![
+            DrawData::FilledRectangle {
+                rect: Rect::new(0, 0, 4, 4),
+                color: Color::YELLOW,
+            }
+            .on_target("sheet"),
+            sheet(0).on_layer(1),
+            DrawData::FilledRectangle {
+                rect: Rect::new(2, 0, 4, 4),
+                color: Color::RED,
+            }
+            .on_layer(1),
+            sheet(4).on_layer(1),
+        ];
+
+        let image = render_offscreen(8, 4, requests, draw_data).unwrap();
+        assert_eq!(image.pixel(1, 0), Some(Color::YELLOW));
+        assert_eq!(image.pixel(3, 0), Some(Color::RED));
+        assert_eq!(image.pixel(5, 0), Some(Color::YELLOW));
+    }
+
+    #[test]
+    fn grid_fonts_without_columns_are_rejected() {
+        let requests = vec![WindowRequest::LoadGridFont {
+            id: "font".to_string(),
+            texture: "font.png".to_string(),
+            char_width: 8,
+            char_height: 8,
+            columns: 0,
+            chars: "AB".to_string(),
+        }];
+        let draw_data: [DrawData; 0] = [];
+        assert!(render_offscreen(8, 8, requests, draw_data).is_err());
+    }
+
+    #[test]
+    fn screenshot_reads_the_letterboxed_viewport() {
+        let path = env::temp_dir().join("minigame-letterbox-screenshot.png");
+        let path = path.to_string_lossy().into_owned();
+        let requests = vec![
+            WindowRequest::SetBackgroundColor(Color::BLUE),
+            WindowRequest::SetLogicalSize(10, 10),
+            WindowRequest::Screenshot { path: path.clone() },
+        ];
+        let draw_data = [
+            DrawData::FilledRectangle {
+                rect: Rect::new(0, 0, 10, 10),
+                color: Color::RED,
+            },
+            DrawData::FilledRectangle {
+                rect: Rect::new(0, 0, 5, 5),
+                color: Color::GREEN,
+            },
+        ];
+
+        // The 40x20 output is scaled by 2 with bars 10 pixels wide on both
+        // sides, which are left out.
+        let image = render_offscreen(40, 20, requests, draw_data).unwrap();
+        let screenshot = Image::load(&path).unwrap();
+        assert_eq!(screenshot, image);
+        assert_eq!((image.width, image.height), (20, 20));
+        assert_eq!(image.pixel(0, 0), Some(Color::GREEN));
+        assert_eq!(image.pixel(9, 9), Some(Color::GREEN));
+        assert_eq!(image.pixel(10, 0), Some(Color::RED));
+        assert_eq!(image.pixel(19, 19), Some(Color::RED));
+        assert_eq!(image.pixel(0, 10), Some(Color::RED));
+    }
+
+    #[test]
+    fn tiles_taller_than_the_map_tiles_stick_out_of_their_chunk() {
+        let image_path = env::temp_dir().join("minigame-tall-tile.png");
+        let image_path = image_path.to_string_lossy().into_owned();
+        let pixels = [0, 255, 0, 255].repeat(16 * 32);
+        Image::new(16, 32, pixels)
+            .unwrap()
+            .save(&image_path)
+            .unwrap();
+
+        let map = Tilemap {
+            width: 1,
+            height: 1,
+            tile_width: 16,
+            tile_height: 16,
+            tilesets: vec![crate::Tileset {
+                first_gid: 1,
+                name: "trees".to_string(),
+                image: image_path,
+                tile_width: 16,
+                tile_height: 32,
+                columns: 1,
+                tile_count: 1,
+                margin: 0,
+                spacing: 0,
+            }],
+            layers: vec![TileLayer {
+                name: "trees".to_string(),
+                width: 1,
+                height: 1,
+                visible: true,
+                opacity: 1.0,
+                tiles: vec![1],
+                properties: Default::default(),
+            }],
+            object_layers: vec![],
+            properties: Default::default(),
+        };
+        let requests = vec![WindowRequest::LoadTilemap {
+            id: "map".to_string(),
+            map,
+        }];
+        let draw_data = [DrawData::Tilemap {
+            id: "map".to_string(),
+            layer: None,
+            x: 0,
+            y: 16,
+        }];
+
+        let image = render_offscreen(16, 32, requests, draw_data).unwrap();
+        assert_eq!(image.pixel(8, 0), Some(Color::GREEN));
+        assert_eq!(image.pixel(8, 31), Some(Color::GREEN));
     }
 }
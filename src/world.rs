@@ -9,76 +9,264 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crate::{DrawData, GameRequest, SharedState};
+use crate::{
+    animation,
+    scene::{self, ActiveScene, SceneChange, Scenes},
+    timer::{self, TimerAction, Timers},
+    tween::{self, Tweens},
+    Camera, Delay, DrawCommand, DrawFrame, EventData, GameRequest, Scene, SharedState, TimerId,
+    Tween, TweenId, WorldRequest,
+};
+
+// The most ticks a single update runs to catch up after falling behind.
+const MAX_CATCH_UP_TICKS: u32 = 5;
+
+// Higher time scales are clamped to this.
+pub const MAX_TIME_SCALE: f32 = 100.0;
 
 pub struct WorldModule {
     ticks_per_second: u16,
+    tick_limit: Option<u64>,
     tick_instant: Instant,
+    accumulator: Duration,
+    elapsed: Duration,
+    last_tick: Option<Instant>,
+    paused_draw_data: Vec<DrawCommand>,
+    camera: Camera,
     world: Rc<UnsafeCell<World>>,
 }
 
 impl<'a> WorldModule {
     pub fn new(ticks_per_second: u16) -> Self {
         WorldModule {
-            ticks_per_second,
+            ticks_per_second: ticks_per_second.max(1),
+            tick_limit: None,
             tick_instant: Instant::now(),
+            accumulator: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            last_tick: None,
+            paused_draw_data: vec![],
+            camera: Camera::default(),
             world: Rc::new(UnsafeCell::new(World::new())),
         }
     }
 
+    // Stops the game once the given number of ticks ran.
+    pub fn set_tick_limit(&mut self, ticks: Option<u64>) {
+        self.tick_limit = ticks;
+    }
+
     pub fn start(&mut self, state: Arc<SharedState>, init: fn(&mut GameHandle)) {
-        init(&mut self.game_handle(state));
+        let mut game = self.game_handle(state);
+        init(&mut game);
+        apply_changes(&mut game);
+        // The first frame starts from the camera set by init.
+        let world = unsafe { &mut *self.world.get() };
+        self.camera = world.resource::<Camera>().copied().unwrap_or_default();
+        world.snap_camera = false;
+        self.tick_instant = Instant::now();
     }
 
+    // Runs as many ticks as the time since the last update calls for, then
+    // sleeps until the next one is due. If the world falls more than
+    // MAX_CATCH_UP_TICKS behind, the rest of the time is dropped, as catching
+    // up on it would only make the following updates fall further behind.
     pub fn update(&mut self, state: Arc<SharedState>) -> Result<(), String> {
+        let now = Instant::now();
+        self.accumulator += now - self.tick_instant;
+        self.tick_instant = now;
+
+        // The tick rate can change during a tick, so the duration is looked
+        // up again after each one.
+        let mut ticks = 0;
+        while self.accumulator >= self.tick_duration() && state.running() {
+            if ticks == MAX_CATCH_UP_TICKS {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+            self.tick(Arc::clone(&state))?;
+            self.accumulator = self.accumulator.saturating_sub(self.tick_duration());
+            ticks += 1;
+        }
+
+        thread::sleep(self.tick_duration().saturating_sub(self.accumulator));
+        Ok(())
+    }
+
+    // Runs a single tick without waiting for the next one.
+    pub fn tick(&mut self, state: Arc<SharedState>) -> Result<(), String> {
+        for request in state.take_world_requests()? {
+            match request {
+                WorldRequest::SetTicksPerSecond(ticks) => self.ticks_per_second = ticks.max(1),
+            }
+        }
+
         // let instant = Instant::now();
 
-        let game = self.game_handle(Arc::clone(&state));
         let world = unsafe { &mut *self.world.get() };
-        world.update(game);
+        let step = mem::take(&mut world.step);
+        let paused = world.paused && !step;
+        let delta = if paused {
+            self.tick_duration()
+        } else {
+            self.tick_duration().mul_f64(world.time_scale as f64)
+        };
+
+        let now = Instant::now();
+        let time = Time {
+            tick: state.tick(),
+            tick_duration: self.tick_duration(),
+            delta,
+            elapsed: self.elapsed,
+            frame_time: self.last_tick.map_or(Duration::ZERO, |last| now - last),
+            time_scale: world.time_scale,
+            paused,
+        };
+        self.last_tick = Some(now);
+        if !paused {
+            self.elapsed += delta;
+        }
+
+        let mut game = self.game_handle(Arc::clone(&state));
+        game.delta = delta;
+        world.insert_resource(time);
+        world.events = state.take_events()?;
+        world.update(game, paused);
 
         // println!("World update took {}us", instant.elapsed().as_micros());
 
-        state.set_draw_data(mem::take(&mut world.draw_data))?;
-        self.await_next_tick();
+        // While paused, what the paused systems drew before is drawn again,
+        // without interpolation.
+        let mut commands = if paused {
+            self.paused_draw_data.clone()
+        } else {
+            mem::take(&mut world.pausable_draw_data)
+        };
+        if world.paused && !paused {
+            self.paused_draw_data = commands
+                .iter()
+                .cloned()
+                .map(|mut command| {
+                    command.data = command.interpolated(1.0);
+                    command.transforms = None;
+                    command
+                })
+                .collect();
+        }
+        commands.append(&mut world.draw_data);
+
+        let camera = world.resource::<Camera>().copied().unwrap_or_default();
+        if mem::take(&mut world.snap_camera) {
+            self.camera = camera;
+        }
+        state.set_draw_data(DrawFrame {
+            commands,
+            camera,
+            previous_camera: mem::replace(&mut self.camera, camera),
+            tick_duration: self.tick_duration(),
+        })?;
+        state.next_tick();
+        if self.tick_limit.is_some_and(|limit| state.tick() >= limit) {
+            state.stop();
+        }
         Ok(())
     }
 
-    fn await_next_tick(&mut self) {
-        let tick_duration = Duration::from_secs(1) / self.ticks_per_second as u32;
-        thread::sleep(tick_duration.saturating_sub(self.tick_instant.elapsed()));
-        self.tick_instant = Instant::now();
+    fn tick_duration(&self) -> Duration {
+        Duration::from_secs(1) / self.ticks_per_second as u32
+    }
+
+    pub(crate) fn world(&self) -> &World {
+        unsafe { &*self.world.get() }
+    }
+
+    pub(crate) fn world_mut(&mut self) -> &mut World {
+        unsafe { &mut *self.world.get() }
     }
 
-    fn game_handle(&'a self, state: Arc<SharedState>) -> GameHandle {
+    pub(crate) fn game_handle(&'a self, state: Arc<SharedState>) -> GameHandle {
         GameHandle {
             world: Rc::clone(&self.world),
+            tick_duration: self.tick_duration(),
+            delta: self.tick_duration(),
             state,
         }
     }
 }
 
+// Inserted as a resource before every tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Time {
+    // The number of ticks completed before this one.
+    pub tick: u64,
+    pub tick_duration: Duration,
+    // The game time that passes during this tick: the tick duration scaled
+    // by the time scale, or unscaled while paused, as only the systems that
+    // run while paused see it.
+    pub delta: Duration,
+    // The game time before this tick, i.e. the sum of all unpaused deltas.
+    pub elapsed: Duration,
+    // The real time since the previous tick started.
+    pub frame_time: Duration,
+    pub time_scale: f32,
+    pub paused: bool,
+}
+
 pub struct World {
     next_entity: Entity,
     entities: Vec<Entity>,
     storage: HashMap<TypeId, Box<dyn WorldStorageTrait>>,
-    draw_data: Vec<DrawData>,
+    resources: HashMap<TypeId, Box<dyn Any>>,
+    events: Vec<EventData>,
+    draw_data: Vec<DrawCommand>,
+    // Drawn by systems that don't run while paused.
+    pausable_draw_data: Vec<DrawCommand>,
+    in_pausable_system: bool,
+    paused: bool,
+    step: bool,
+    time_scale: f32,
+    snap_camera: bool,
+    timers: Timers,
+    tweens: Tweens,
+    scenes: Scenes,
+    removed_entities: Vec<Entity>,
 }
 
 impl<'a> World {
     pub fn new() -> Self {
-        World {
+        let mut world = World {
             next_entity: Entity::default(),
             entities: vec![],
             storage: HashMap::new(),
+            resources: HashMap::new(),
+            events: vec![],
             draw_data: vec![],
-        }
+            pausable_draw_data: vec![],
+            in_pausable_system: false,
+            paused: false,
+            step: false,
+            time_scale: 1.0,
+            snap_camera: false,
+            timers: Timers::default(),
+            tweens: Tweens::default(),
+            scenes: Scenes::default(),
+            removed_entities: vec![],
+        };
+        world.add_system(animation::animate);
+        world.add_system(timer::update_timer);
+        world
     }
 
-    pub fn update(&mut self, mut game: GameHandle) {
+    pub fn update(&mut self, mut game: GameHandle, paused: bool) {
         for storage in self.storage.values_mut() {
-            storage.update(&mut game);
+            storage.update(&mut game, paused);
         }
+        self.in_pausable_system = false;
+        if !paused {
+            timer::run_timers(&mut game);
+            tween::run_tweens(&mut game);
+        }
+        apply_changes(&mut game);
     }
 
     fn storage<C: 'static>(&mut self) -> &mut WorldStorage<C> {
@@ -90,24 +278,108 @@ impl<'a> World {
         storage.downcast_mut().unwrap()
     }
 
+    fn get_storage<C: 'static>(&self) -> Option<&WorldStorage<C>> {
+        let storage = self.storage.get(&TypeId::of::<C>())?;
+        let storage: &Box<dyn Any> = unsafe { mem::transmute(storage) };
+        storage.downcast_ref()
+    }
+
+    fn get_storage_mut<C: 'static>(&mut self) -> Option<&mut WorldStorage<C>> {
+        let storage = self.storage.get_mut(&TypeId::of::<C>())?;
+        let storage: &mut Box<dyn Any> = unsafe { mem::transmute(storage) };
+        storage.downcast_mut()
+    }
+
+    // The entity belongs to the scene on top of the stack, if any.
     pub fn add_entity(&mut self) -> Entity {
+        let entity = self.add_persistent_entity();
+        if let Some(scene) = self.scenes.stack.last_mut() {
+            scene.entities.push(entity);
+        }
+        entity
+    }
+
+    pub fn add_persistent_entity(&mut self) -> Entity {
         let entity = self.next_entity;
         self.next_entity = entity.next();
         self.entities.push(entity);
         entity
     }
 
+    // Must not be called while systems are running, as the components of
+    // the running storage are taken out of it.
+    pub fn remove_entity(&mut self, entity: Entity) {
+        self.entities.retain(|&e| e != entity);
+        for storage in self.storage.values_mut() {
+            storage.remove_entity(entity);
+        }
+        for scene in self.scenes.stack.iter_mut() {
+            scene.entities.retain(|&e| e != entity);
+        }
+    }
+
     pub fn add_system<C: 'static>(&mut self, system: fn(&mut GameHandle, Entity, &mut C)) {
         self.storage::<C>().add_system(system);
     }
 
+    pub fn add_unpausable_system<C: 'static>(
+        &mut self,
+        system: fn(&mut GameHandle, Entity, &mut C),
+    ) {
+        self.storage::<C>().add_unpausable_system(system);
+    }
+
     pub fn add_component<C: 'static>(&mut self, entity: Entity, component: C) {
         self.storage::<C>().add_component(entity, component);
     }
+
+    pub fn component<C: 'static>(&self, entity: Entity) -> Option<&C> {
+        self.components()
+            .find(|(e, _)| *e == entity)
+            .map(|(_, c)| c)
+    }
+
+    pub fn component_mut<C: 'static>(&mut self, entity: Entity) -> Option<&mut C> {
+        let storage = self.get_storage_mut::<C>()?;
+        let index = storage.entities.iter().position(|&e| e == entity)?;
+        storage.components.get_mut(index)
+    }
+
+    pub fn components<C: 'static>(&self) -> impl Iterator<Item = (Entity, &C)> {
+        self.get_storage::<C>()
+            .into_iter()
+            .flat_map(|storage| storage.entities.iter().copied().zip(&storage.components))
+    }
+
+    pub fn insert_resource<R: 'static>(&mut self, resource: R) {
+        self.resources.insert(TypeId::of::<R>(), Box::new(resource));
+    }
+
+    pub fn remove_resource<R: 'static>(&mut self) -> Option<R> {
+        self.resources
+            .remove(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast().ok())
+            .map(|resource| *resource)
+    }
+
+    pub fn resource<R: 'static>(&self) -> Option<&R> {
+        self.resources
+            .get(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast_ref())
+    }
+
+    pub fn resource_mut<R: 'static>(&mut self) -> Option<&mut R> {
+        self.resources
+            .get_mut(&TypeId::of::<R>())
+            .and_then(|resource| resource.downcast_mut())
+    }
 }
 
+pub type System<C> = fn(&mut GameHandle, Entity, &mut C);
+
 pub struct WorldStorage<C> {
-    systems: Vec<fn(&mut GameHandle, Entity, &mut C)>,
+    // Systems marked true keep running while the world is paused.
+    systems: Vec<(System<C>, bool)>,
     components: Vec<C>,
     entities: Vec<Entity>,
 }
@@ -122,7 +394,11 @@ impl<C> WorldStorage<C> {
     }
 
     pub fn add_system(&mut self, system: fn(&mut GameHandle, Entity, &mut C)) {
-        self.systems.push(system);
+        self.systems.push((system, false));
+    }
+
+    pub fn add_unpausable_system(&mut self, system: fn(&mut GameHandle, Entity, &mut C)) {
+        self.systems.push((system, true));
     }
 
     pub fn clear_systems(&mut self) {
@@ -148,16 +424,23 @@ impl<C> WorldStorage<C> {
 }
 
 pub trait WorldStorageTrait: Any {
-    fn update(&mut self, game: &mut GameHandle);
+    fn update(&mut self, game: &mut GameHandle, paused: bool);
+    fn remove_entity(&mut self, entity: Entity);
+    fn system_count(&self) -> usize;
+    fn truncate_systems(&mut self, count: usize);
 }
 
 impl<C: 'static> WorldStorageTrait for WorldStorage<C> {
-    fn update(&mut self, game: &mut GameHandle) {
+    fn update(&mut self, game: &mut GameHandle, paused: bool) {
         let mut systems = mem::take(&mut self.systems);
         let mut components = mem::take(&mut self.components);
         let mut entities = mem::take(&mut self.entities);
 
-        for system in systems.iter() {
+        for (system, unpausable) in systems.iter() {
+            if paused && !unpausable {
+                continue;
+            }
+            game.world_mut().in_pausable_system = !unpausable;
             for (entity, component) in entities.iter().zip(components.iter_mut()) {
                 system(game, *entity, component);
             }
@@ -167,10 +450,36 @@ impl<C: 'static> WorldStorageTrait for WorldStorage<C> {
         self.components = mem::take(&mut components);
         self.entities = mem::take(&mut entities);
     }
+
+    fn remove_entity(&mut self, entity: Entity) {
+        while self.entities.contains(&entity) {
+            self.remove_component(entity);
+        }
+    }
+
+    fn system_count(&self) -> usize {
+        self.systems.len()
+    }
+
+    fn truncate_systems(&mut self, count: usize) {
+        self.systems.truncate(count);
+    }
+}
+
+// Removes the entities queued for removal and enters and exits scenes. Runs at
+// the end of every tick and after init.
+fn apply_changes(game: &mut GameHandle) {
+    let world = game.world_mut();
+    for entity in mem::take(&mut world.removed_entities) {
+        world.remove_entity(entity);
+    }
+    scene::apply_scene_changes(game);
 }
 
 pub struct GameHandle {
     world: Rc<UnsafeCell<World>>,
+    tick_duration: Duration,
+    delta: Duration,
     state: Arc<SharedState>,
 }
 
@@ -179,14 +488,46 @@ impl GameHandle {
         self.state.stop();
     }
 
+    pub fn tick_duration(&self) -> Duration {
+        self.tick_duration
+    }
+
+    // The number of ticks completed before the current one.
+    pub fn tick(&self) -> u64 {
+        self.state.tick()
+    }
+
+    // The game time that passes during the tick in seconds, for scaling
+    // movement and the like. Unlike the tick duration, it follows the time
+    // scale.
+    pub fn delta(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub(crate) fn delta_duration(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn push_event(&self, event: EventData) -> Result<(), String> {
+        self.state.push_event(event)
+    }
+
     pub fn send<R: GameRequest>(&self, request: R) -> Result<(), String> {
         request.send(&self.state)?;
         Ok(())
     }
 
-    pub fn draw(&mut self, data: DrawData) {
-        let world = unsafe { &mut *self.world.get() };
-        world.draw_data.push(data);
+    fn world_mut(&mut self) -> &mut World {
+        unsafe { &mut *self.world.get() }
+    }
+
+    pub fn draw<D: Into<DrawCommand>>(&mut self, data: D) {
+        let world = self.world_mut();
+        if world.in_pausable_system {
+            world.pausable_draw_data.push(data.into());
+        } else {
+            world.draw_data.push(data.into());
+        }
     }
 
     pub fn add_system<C: 'static>(&mut self, system: fn(&mut GameHandle, Entity, &mut C)) {
@@ -194,6 +535,154 @@ impl GameHandle {
         world.add_system(system);
     }
 
+    // Adds a system that keeps running while the world is paused, e.g. for
+    // menus.
+    pub fn add_unpausable_system<C: 'static>(
+        &mut self,
+        system: fn(&mut GameHandle, Entity, &mut C),
+    ) {
+        let world = unsafe { &mut *self.world.get() };
+        world.add_unpausable_system(system);
+    }
+
+    pub fn events(&self) -> &[EventData] {
+        let world = unsafe { &*self.world.get() };
+        &world.events
+    }
+
+    pub fn insert_resource<R: 'static>(&mut self, resource: R) {
+        let world = unsafe { &mut *self.world.get() };
+        world.insert_resource(resource);
+    }
+
+    pub fn remove_resource<R: 'static>(&mut self) -> Option<R> {
+        let world = unsafe { &mut *self.world.get() };
+        world.remove_resource()
+    }
+
+    pub fn resource<R: 'static>(&self) -> Option<&R> {
+        let world = unsafe { &*self.world.get() };
+        world.resource()
+    }
+
+    pub fn resource_mut<R: 'static>(&mut self) -> Option<&mut R> {
+        let world = unsafe { &mut *self.world.get() };
+        world.resource_mut()
+    }
+
+    pub fn time(&self) -> Time {
+        self.resource::<Time>().copied().unwrap_or_default()
+    }
+
+    // Pausing, resuming and the time scale take effect from the next tick.
+    pub fn pause(&mut self) {
+        self.world_mut().paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.world_mut().paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        let world = unsafe { &*self.world.get() };
+        world.paused
+    }
+
+    // Runs all systems for a single tick while paused.
+    pub fn step(&mut self) {
+        self.world_mut().step = true;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        let world = unsafe { &*self.world.get() };
+        world.time_scale
+    }
+
+    // The scale is clamped to 0 to MAX_TIME_SCALE, and NaN is ignored.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        if !scale.is_nan() {
+            self.world_mut().time_scale = scale.clamp(0.0, MAX_TIME_SCALE);
+        }
+    }
+
+    // Timers scheduled while a scene is on top of the stack are cancelled
+    // when it exits.
+    pub fn after<D: Into<Delay>>(&mut self, delay: D, callback: fn(&mut GameHandle)) -> TimerId {
+        self.schedule_timer(delay.into(), false, TimerAction::Callback(callback))
+    }
+
+    pub fn every<D: Into<Delay>>(&mut self, interval: D, callback: fn(&mut GameHandle)) -> TimerId {
+        self.schedule_timer(interval.into(), true, TimerAction::Callback(callback))
+    }
+
+    // Like `after` and `every`, but push an `EventData::TimerFired` with the
+    // returned id instead of calling back.
+    pub fn event_after<D: Into<Delay>>(&mut self, delay: D) -> TimerId {
+        self.schedule_timer(delay.into(), false, TimerAction::Event)
+    }
+
+    pub fn event_every<D: Into<Delay>>(&mut self, interval: D) -> TimerId {
+        self.schedule_timer(interval.into(), true, TimerAction::Event)
+    }
+
+    fn schedule_timer(&mut self, delay: Delay, repeat: bool, action: TimerAction) -> TimerId {
+        let id = self.timers().schedule(delay, repeat, action);
+        if let Some(scene) = self.scenes().stack.last_mut() {
+            scene.timers.push(id);
+        }
+        id
+    }
+
+    pub fn cancel_timer(&mut self, id: TimerId) {
+        self.timers().cancel(id);
+    }
+
+    pub(crate) fn timers(&mut self) -> &mut Timers {
+        &mut self.world_mut().timers
+    }
+
+    // Tweens run on the entity's component of type C, after the systems and
+    // timers of every unpaused tick.
+    pub fn tween<C: 'static>(&mut self, entity: Entity, tween: Tween<C>) -> TweenId {
+        self.tweens().start(entity, tween)
+    }
+
+    pub fn cancel_tween(&mut self, id: TweenId) {
+        self.tweens().cancel(id);
+    }
+
+    pub(crate) fn tweens(&mut self) -> &mut Tweens {
+        &mut self.world_mut().tweens
+    }
+
+    // Components of the type whose systems are running can't be accessed.
+    pub fn component<C: 'static>(&self, entity: Entity) -> Option<&C> {
+        let world = unsafe { &*self.world.get() };
+        world.component(entity)
+    }
+
+    pub fn component_mut<C: 'static>(&mut self, entity: Entity) -> Option<&mut C> {
+        self.world_mut().component_mut(entity)
+    }
+
+    pub fn camera(&self) -> Camera {
+        self.resource::<Camera>().copied().unwrap_or_default()
+    }
+
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.insert_resource(camera);
+    }
+
+    // Moves the camera to where it is at the end of the tick without
+    // interpolating from where it was, e.g. when switching scenes.
+    pub fn snap_camera(&mut self) {
+        self.world_mut().snap_camera = true;
+    }
+
+    pub fn screen_to_world(&self, x: i32, y: i32) -> (f32, f32) {
+        self.camera().screen_to_world(x as f32, y as f32)
+    }
+
     pub fn add_entity(&mut self) -> EntityHandle {
         let world = unsafe { &mut *self.world.get() };
         let entity = world.add_entity();
@@ -202,9 +691,64 @@ impl GameHandle {
             entity,
         }
     }
+
+    // Adds an entity that outlives the current scene.
+    pub fn add_persistent_entity(&mut self) -> EntityHandle {
+        let world = unsafe { &mut *self.world.get() };
+        let entity = world.add_persistent_entity();
+        EntityHandle {
+            world: Rc::clone(&self.world),
+            entity,
+        }
+    }
+
+    // The entity is removed at the end of the tick.
+    pub fn remove_entity(&mut self, entity: Entity) {
+        self.world_mut().removed_entities.push(entity);
+    }
+
+    // Scene changes take effect at the end of the tick. Entering a scene
+    // keeps the scenes below it, e.g. for a pause menu, while switching
+    // replaces the scene on top.
+    pub fn push_scene(&mut self, scene: Scene) {
+        self.scenes().change(SceneChange::Push(scene));
+    }
+
+    pub fn pop_scene(&mut self) {
+        self.scenes().change(SceneChange::Pop);
+    }
+
+    pub fn switch_scene(&mut self, scene: Scene) {
+        self.scenes().change(SceneChange::Switch(scene));
+    }
+
+    pub(crate) fn scenes(&mut self) -> &mut Scenes {
+        &mut self.world_mut().scenes
+    }
+
+    pub(crate) fn system_counts(&mut self) -> HashMap<TypeId, usize> {
+        self.world_mut()
+            .storage
+            .iter()
+            .map(|(id, storage)| (*id, storage.system_count()))
+            .collect()
+    }
+
+    pub(crate) fn remove_scene(&mut self, scene: ActiveScene) {
+        let world = self.world_mut();
+        for (id, storage) in world.storage.iter_mut() {
+            storage.truncate_systems(scene.system_counts.get(id).copied().unwrap_or(0));
+        }
+        for entity in scene.entities {
+            world.remove_entity(entity);
+        }
+        for id in scene.timers {
+            world.timers.cancel(id);
+        }
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Entity(u64);
 
 impl Entity {
@@ -235,3 +779,125 @@ impl EntityHandle {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestHarness;
+
+    struct Ticks(u32);
+
+    fn count(_: &mut GameHandle, _: Entity, ticks: &mut Ticks) {
+        ticks.0 += 1;
+    }
+
+    fn init(game: &mut GameHandle) {
+        game.add_entity().add_component(Ticks(0));
+        game.add_system(count);
+    }
+
+    fn ticks(harness: &TestHarness) -> u32 {
+        harness.components::<Ticks>().next().unwrap().1 .0
+    }
+
+    #[test]
+    fn step_runs_one_paused_tick() {
+        let mut harness = TestHarness::new(init).unwrap();
+        harness.game().pause();
+        harness.ticks(2).unwrap();
+        assert_eq!(ticks(&harness), 0);
+
+        harness.game().step();
+        harness.ticks(2).unwrap();
+        assert_eq!(ticks(&harness), 1);
+        assert!(harness.game().is_paused());
+    }
+
+    #[test]
+    fn step_while_running_is_not_kept_for_later() {
+        let mut harness = TestHarness::new(init).unwrap();
+        harness.game().step();
+        harness.tick().unwrap();
+        assert_eq!(ticks(&harness), 1);
+
+        harness.game().pause();
+        harness.tick().unwrap();
+        assert_eq!(ticks(&harness), 1);
+    }
+
+    #[test]
+    fn time_scale_is_clamped() {
+        let mut harness = TestHarness::new(init).unwrap();
+        let mut game = harness.game();
+        game.set_time_scale(2.0);
+        assert_eq!(game.time_scale(), 2.0);
+        game.set_time_scale(f32::NAN);
+        assert_eq!(game.time_scale(), 2.0);
+        game.set_time_scale(-1.0);
+        assert_eq!(game.time_scale(), 0.0);
+        game.set_time_scale(f32::INFINITY);
+        assert_eq!(game.time_scale(), MAX_TIME_SCALE);
+        game.set_time_scale(1e30);
+        assert_eq!(game.time_scale(), MAX_TIME_SCALE);
+    }
+
+    #[test]
+    fn only_delta_follows_the_time_scale() {
+        fn check(game: &mut GameHandle, _: Entity, _: &mut Ticks) {
+            let time = game.time();
+            assert_eq!(game.tick_duration(), Duration::from_millis(50));
+            assert_eq!(time.tick_duration, game.tick_duration());
+            assert_eq!(game.delta(), 0.025);
+            assert_eq!(time.delta, Duration::from_millis(25));
+        }
+
+        let mut harness = TestHarness::new(init).unwrap();
+        harness.game().set_time_scale(0.5);
+        harness.game().add_system(check);
+        harness.ticks(2).unwrap();
+        assert_eq!(ticks(&harness), 2);
+    }
+
+    #[test]
+    fn tick_limit_stops_catching_up() {
+        let state = Arc::new(SharedState::new());
+        let mut world = WorldModule::new(1000);
+        world.set_tick_limit(Some(2));
+        world.start(Arc::clone(&state), |_| ());
+
+        // Far enough behind to catch up on MAX_CATCH_UP_TICKS ticks.
+        thread::sleep(Duration::from_millis(20));
+        world.update(Arc::clone(&state)).unwrap();
+        assert_eq!(state.tick(), 2);
+        assert!(!state.running());
+    }
+
+    fn camera_at(x: f32) -> Camera {
+        Camera {
+            x,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn first_frame_starts_from_the_camera_set_by_init() {
+        let mut harness = TestHarness::new(|game| game.set_camera(camera_at(100.0))).unwrap();
+        harness.tick().unwrap();
+        assert_eq!(harness.frame().previous_camera, camera_at(100.0));
+        assert_eq!(harness.frame().camera, camera_at(100.0));
+    }
+
+    #[test]
+    fn snapped_cameras_are_not_interpolated() {
+        let mut harness = TestHarness::new(|_| ()).unwrap();
+        harness.game().set_camera(camera_at(100.0));
+        harness.game().snap_camera();
+        harness.tick().unwrap();
+        assert_eq!(harness.frame().previous_camera, camera_at(100.0));
+
+        harness.game().set_camera(camera_at(200.0));
+        harness.tick().unwrap();
+        assert_eq!(harness.frame().previous_camera, camera_at(100.0));
+        assert_eq!(harness.frame().camera, camera_at(200.0));
+    }
+}